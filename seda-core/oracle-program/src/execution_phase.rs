@@ -14,8 +14,24 @@ use serde_json::json;
 const RPC_URL: &str =
     "https://cronos.blockpi.network/v1/rpc/0467a344ecda6f87cc7118bd02a14f5818a2f5ff";
 
+/// Independent Cronos RPC endpoints the whole pipeline is run against. No
+/// single provider is trusted: at least [`RPC_QUORUM`] of them must return
+/// usable data. The final numeric output fields are each the median of the
+/// independently computed per-endpoint values, and `flags` is the bitwise
+/// OR of every endpoint's flags.
+const RPC_URLS: &[&str] = &[
+    RPC_URL,
+    "https://mainnet-sticky.cronoslabs.com/v1/d3642384d334ff6ff1c4baebfdf3ef7d",
+    "https://evm.cronos.org",
+];
+const RPC_QUORUM: usize = 2;
+
 const SELECTOR_GET_RESERVES: &str = "0902f1ac";
 const SELECTOR_TOKEN0: &str = "0dfe1681";
+/// Cumulative price of token0, denominated in token1, UQ112x112 fixed point.
+const SELECTOR_PRICE0_CUMULATIVE: &str = "5909c0d5";
+/// Cumulative price of token1, denominated in token0, UQ112x112 fixed point.
+const SELECTOR_PRICE1_CUMULATIVE: &str = "5a3d5493";
 
 const WCRO_ADDRESS: &str = "0x5C7F8A570d578ED84E63fdFA7b1eE72dEae1AE23";
 const USDC_ADDRESS: &str = "0xc21223249CA28397B4B6541dfFaEcC539BfF0c59";
@@ -29,6 +45,20 @@ const CONFIDENCE_WARN_SCORE: u128 = 200_000;
 const DIVERGENCE_WARN_1E6: u128 = 50_000;
 const SLIPPAGE_LIMIT_1E6: u128 = 10_000;
 const TARGET_PAIR: &str = "WCRO-USDC";
+const GAS_PAIR: &str = "GAS-CRO";
+
+/// EIP-1559 elasticity multiplier: the target gas usage per block is half
+/// of the block gas limit.
+const ELASTICITY_MULTIPLIER: u64 = 2;
+/// Number of trailing blocks `eth_feeHistory` is sampled over for the
+/// suggested priority fee.
+const FEE_HISTORY_BLOCK_COUNT: u64 = 20;
+/// Reward percentile requested from `eth_feeHistory` for the priority fee.
+const FEE_HISTORY_PERCENTILE: f64 = 50.0;
+
+/// Default MAD multiplier for the tally phase's outlier rejection; see
+/// `parse_input_pair`.
+const DEFAULT_OUTLIER_K: u8 = 3;
 
 pub fn execution_phase() -> Result<()> {
     if let Err(err) = execution_phase_inner() {
@@ -41,9 +71,13 @@ pub fn execution_phase() -> Result<()> {
 
 fn execution_phase_inner() -> Result<()> {
     let input = String::from_utf8(Process::get_inputs())?;
-    let pair = parse_input_pair(&input)?;
+    let (pair, method_tag, method_param, outlier_k) = parse_input_pair(&input)?;
     log!("Requested pair: {pair}");
 
+    if pair.as_str() == GAS_PAIR {
+        return execution_phase_gas(method_tag, method_param, outlier_k);
+    }
+
     let pair_config = PairConfig {
         pair: WCRO_USDC_PAIR,
         base: WCRO_ADDRESS,
@@ -52,21 +86,100 @@ fn execution_phase_inner() -> Result<()> {
         quote_decimals: 6,
     };
 
-    let token0_result = rpc_call(pair_config.pair, SELECTOR_TOKEN0, None)?;
+    let mut reports = Vec::with_capacity(RPC_URLS.len());
+    for &rpc_url in RPC_URLS {
+        match compute_report(rpc_url, &pair_config) {
+            Ok(report) => reports.push(report),
+            Err(err) => elog!("RPC endpoint {rpc_url} failed: {err}"),
+        }
+    }
+
+    if reports.len() < RPC_QUORUM {
+        return Err(anyhow!(
+            "Only {} of {} RPC endpoints returned data, need quorum of {RPC_QUORUM}",
+            reports.len(),
+            RPC_URLS.len()
+        ));
+    }
+
+    let fair_price = median_u128(&reports.iter().map(|r| r.fair_price).collect::<Vec<_>>());
+    let confidence_score =
+        median_u128(&reports.iter().map(|r| r.confidence_score).collect::<Vec<_>>());
+    let max_safe_execution_size = median_u128(
+        &reports
+            .iter()
+            .map(|r| r.max_safe_execution_size)
+            .collect::<Vec<_>>(),
+    );
+    // `flags` is a bitmask, not a magnitude: a median across endpoints would
+    // silently drop whichever warnings only some endpoints raised. OR them
+    // together instead, so any endpoint raising a warning bit keeps it set
+    // in the final report.
+    let flags = reports.iter().fold(0u128, |acc, r| acc | r.flags);
+
+    let values = vec![
+        U256::from(method_tag),
+        U256::from(method_param),
+        U256::from(outlier_k),
+        U256::from(fair_price),
+        U256::from(confidence_score),
+        U256::from(max_safe_execution_size),
+        U256::from(flags),
+    ];
+
+    let encoded = ethabi::encode(&[ethabi::Token::Array(
+        values
+            .into_iter()
+            .map(ethabi::Token::Int)
+            .collect(),
+    )]);
+
+    log!(
+        "fair_price: {fair_price}, confidence: {confidence_score}, max_size: {max_safe_execution_size}, flags: {flags}"
+    );
+    Process::success(&encoded);
+}
+
+struct PriceReport {
+    fair_price: u128,
+    confidence_score: u128,
+    max_safe_execution_size: u128,
+    flags: u128,
+}
+
+/// Runs the full fair-price computation against a single RPC endpoint.
+fn compute_report(rpc_url: &str, pair_config: &PairConfig) -> Result<PriceReport> {
+    let token0_result = rpc_call(rpc_url, pair_config.pair, SELECTOR_TOKEN0, None)?;
     let token0 = parse_address_from_32byte(&token0_result)
         .ok_or_else(|| anyhow!("Failed to parse token0 address"))?;
 
-    let latest_block = rpc_get_block_number()?;
+    let latest_block = rpc_get_block_number(rpc_url)?;
     let block_24h = latest_block.saturating_sub(BLOCKS_24H_ESTIMATE);
-    let latest_reserves = get_reserves(pair_config.pair, Some(latest_block))?;
-    let spot_now = price_from_reserves(&pair_config, &token0, &latest_reserves)?;
-
-    let reserves_24h = get_reserves(pair_config.pair, Some(block_24h))?;
-    let price_24h = price_from_reserves(&pair_config, &token0, &reserves_24h)?;
+    let latest_reserves = get_reserves(rpc_url, pair_config.pair, Some(latest_block))?;
+    let spot_now = price_from_reserves(pair_config, &token0, &latest_reserves)?;
+
+    let reserves_24h = get_reserves(rpc_url, pair_config.pair, Some(block_24h))?;
+    let spot_24h = price_from_reserves(pair_config, &token0, &reserves_24h)?;
+
+    // The real 24h reference price is the on-chain TWAP between the two
+    // snapshots, not either snapshot's instantaneous reserve ratio: a
+    // single manipulated block can't move an accumulator integrated over
+    // ~24h the way it can move a point-in-time reserve ratio.
+    let cumulative_now = fetch_cumulative_price(rpc_url, pair_config, &token0, latest_block)?;
+    let cumulative_24h = fetch_cumulative_price(rpc_url, pair_config, &token0, block_24h)?;
+    let price_24h = twap_price(
+        pair_config,
+        cumulative_now,
+        cumulative_24h,
+        latest_reserves.block_timestamp_last,
+        reserves_24h.block_timestamp_last,
+        spot_24h,
+    )?;
 
     let fair_price = (spot_now.saturating_mul(2) + price_24h) / 3;
 
-    let liquidity_score = liquidity_score(latest_reserves.quote_reserve(&token0, &pair_config)?)?;
+    let liquidity_score =
+        liquidity_score(latest_reserves.quote_reserve(&token0, pair_config)?)?;
     let delta_1e6 = ratio_scaled_u128(abs_diff_u128(spot_now, price_24h), spot_now)?;
     let time_score = temporal_score(delta_1e6);
     let confidence_score = (U256::from(600_000u128) * U256::from(liquidity_score)
@@ -75,36 +188,181 @@ fn execution_phase_inner() -> Result<()> {
     let confidence_score = confidence_score.as_u128();
 
     let max_safe_execution_size = max_safe_execution_size(
-        latest_reserves.quote_reserve(&token0, &pair_config)?,
-        latest_reserves.base_reserve(&token0, &pair_config)?,
+        latest_reserves.quote_reserve(&token0, pair_config)?,
+        latest_reserves.base_reserve(&token0, pair_config)?,
         spot_now,
     )?;
 
     let flags = build_flags(delta_1e6, liquidity_score, confidence_score);
 
+    Ok(PriceReport {
+        fair_price,
+        confidence_score,
+        max_safe_execution_size,
+        flags,
+    })
+}
+
+/// Fee estimation for the `GAS-CRO` input mode: the predicted next-block
+/// EIP-1559 base fee, plus a suggested priority fee.
+fn execution_phase_gas(method_tag: u8, method_param: u8, outlier_k: u8) -> Result<()> {
+    let mut reports = Vec::with_capacity(RPC_URLS.len());
+    for &rpc_url in RPC_URLS {
+        match compute_gas_report(rpc_url) {
+            Ok(report) => reports.push(report),
+            Err(err) => elog!("RPC endpoint {rpc_url} failed: {err}"),
+        }
+    }
+
+    if reports.len() < RPC_QUORUM {
+        return Err(anyhow!(
+            "Only {} of {} RPC endpoints returned data, need quorum of {RPC_QUORUM}",
+            reports.len(),
+            RPC_URLS.len()
+        ));
+    }
+
+    let next_base_fee =
+        median_u128(&reports.iter().map(|r| r.next_base_fee).collect::<Vec<_>>());
+    let priority_fee = median_u128(&reports.iter().map(|r| r.priority_fee).collect::<Vec<_>>());
+
     let values = vec![
-        U256::from(fair_price),
-        U256::from(confidence_score),
-        U256::from(max_safe_execution_size),
-        U256::from(flags),
+        U256::from(method_tag),
+        U256::from(method_param),
+        U256::from(outlier_k),
+        U256::from(next_base_fee),
+        U256::from(priority_fee),
     ];
 
     let encoded = ethabi::encode(&[ethabi::Token::Array(
-        values
-            .into_iter()
-            .map(ethabi::Token::Int)
-            .collect(),
+        values.into_iter().map(ethabi::Token::Int).collect(),
     )]);
 
-    log!(
-        "fair_price: {fair_price}, confidence: {confidence_score}, max_size: {max_safe_execution_size}, flags: {flags}"
-    );
+    log!("next_base_fee: {next_base_fee}, priority_fee: {priority_fee}");
     Process::success(&encoded);
 }
 
+struct GasReport {
+    next_base_fee: u128,
+    priority_fee: u128,
+}
+
+/// Runs the full gas-fee estimation against a single RPC endpoint.
+fn compute_gas_report(rpc_url: &str) -> Result<GasReport> {
+    let (base_fee, gas_used, gas_limit) = fetch_latest_block_gas(rpc_url)?;
+    let next_base_fee = next_base_fee(base_fee, gas_used, gas_limit)?;
+    let priority_fee = fetch_priority_fee(rpc_url)?;
+
+    Ok(GasReport {
+        next_base_fee,
+        priority_fee,
+    })
+}
+
+/// Reads `baseFeePerGas`, `gasUsed`, and `gasLimit` off the latest block.
+fn fetch_latest_block_gas(rpc_url: &str) -> Result<(U256, U256, U256)> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getBlockByNumber",
+        "params": ["latest", false]
+    });
+
+    let json_value = rpc_request(rpc_url, body)?;
+    if let Some(error) = json_value.get("error") {
+        return Err(anyhow!("RPC error: {error}"));
+    }
+    let block = json_value
+        .get("result")
+        .ok_or_else(|| anyhow!("RPC response missing result"))?;
+
+    let base_fee = hex_field_to_u256(block, "baseFeePerGas")?;
+    let gas_used = hex_field_to_u256(block, "gasUsed")?;
+    let gas_limit = hex_field_to_u256(block, "gasLimit")?;
+    Ok((base_fee, gas_used, gas_limit))
+}
+
+fn hex_field_to_u256(value: &serde_json::Value, field: &str) -> Result<U256> {
+    let hex = value
+        .get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing {field} in block"))?;
+    let bytes = hex_to_bytes(hex).ok_or_else(|| anyhow!("Invalid hex for {field}"))?;
+    Ok(u256_from_be_slice(&bytes))
+}
+
+/// EIP-1559 base-fee recurrence: unchanged at the target, otherwise nudged
+/// by up to 1/8th of the current base fee per unit of over/under-shoot,
+/// floored at a 1 wei increase when over target and at zero when under.
+fn next_base_fee(base_fee: U256, gas_used: U256, gas_limit: U256) -> Result<u128> {
+    let target = gas_limit / U256::from(ELASTICITY_MULTIPLIER);
+    if target.is_zero() {
+        return Err(anyhow!("Gas limit target is zero"));
+    }
+
+    let next = match gas_used.cmp(&target) {
+        std::cmp::Ordering::Equal => base_fee,
+        std::cmp::Ordering::Greater => {
+            let delta = gas_used - target;
+            let increase = std::cmp::max(U256::one(), base_fee * delta / target / U256::from(8u8));
+            base_fee.saturating_add(increase)
+        }
+        std::cmp::Ordering::Less => {
+            let delta = target - gas_used;
+            let decrease = base_fee * delta / target / U256::from(8u8);
+            base_fee.saturating_sub(decrease)
+        }
+    };
+
+    u256_to_u128(next)
+}
+
+/// Suggested priority fee: the median of the 50th-percentile rewards over
+/// the last [`FEE_HISTORY_BLOCK_COUNT`] blocks from `eth_feeHistory`.
+fn fetch_priority_fee(rpc_url: &str) -> Result<u128> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_feeHistory",
+        "params": [
+            format!("0x{FEE_HISTORY_BLOCK_COUNT:x}"),
+            "latest",
+            [FEE_HISTORY_PERCENTILE]
+        ]
+    });
+
+    let json_value = rpc_request(rpc_url, body)?;
+    if let Some(error) = json_value.get("error") {
+        return Err(anyhow!("RPC error: {error}"));
+    }
+    let rewards = json_value
+        .get("result")
+        .and_then(|result| result.get("reward"))
+        .and_then(|reward| reward.as_array())
+        .ok_or_else(|| anyhow!("RPC response missing reward history"))?;
+
+    let mut samples = Vec::with_capacity(rewards.len());
+    for reward in rewards {
+        let hex = reward
+            .as_array()
+            .and_then(|percentiles| percentiles.first())
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| anyhow!("Malformed fee history reward entry"))?;
+        let bytes = hex_to_bytes(hex).ok_or_else(|| anyhow!("Invalid hex reward"))?;
+        samples.push(u256_to_u128(u256_from_be_slice(&bytes))?);
+    }
+
+    if samples.is_empty() {
+        return Err(anyhow!("No fee history reward samples"));
+    }
+    Ok(median_u128(&samples))
+}
+
 #[derive(Deserialize)]
 struct OracleInput {
     pair: Option<String>,
+    method: Option<String>,
+    outlier_k: Option<u8>,
 }
 
 struct PairConfig {
@@ -115,7 +373,12 @@ struct PairConfig {
     quote_decimals: u8,
 }
 
-fn parse_input_pair(input: &str) -> Result<String> {
+/// Parses the data request input, returning `(pair, method_tag, method_param,
+/// outlier_k)`. `outlier_k` is the MAD multiplier the tally phase uses to
+/// reject outlier reveals before aggregating (see `parse_aggregation_method`
+/// for the other two fields); it defaults to [`DEFAULT_OUTLIER_K`] and `0`
+/// disables the filter.
+fn parse_input_pair(input: &str) -> Result<(String, u8, u8, u8)> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
         return Err(anyhow!("Missing input: pair required"));
@@ -127,16 +390,56 @@ fn parse_input_pair(input: &str) -> Result<String> {
         .context("Missing pair in input")?
         .to_uppercase();
 
-    if pair.as_str() != TARGET_PAIR {
+    if pair.as_str() != TARGET_PAIR && pair.as_str() != GAS_PAIR {
         return Err(anyhow!("Unsupported pair: {pair}"));
     }
 
-    Ok(pair)
+    let (method_tag, method_param) = parse_aggregation_method(parsed.method.as_deref())?;
+    let outlier_k = parsed.outlier_k.unwrap_or(DEFAULT_OUTLIER_K);
+    Ok((pair, method_tag, method_param, outlier_k))
+}
+
+/// Parses the optional `"method"` field of the data request input into the
+/// `(tag, param)` pair prepended to every reveal, so the tally phase can
+/// aggregate with the method the requester asked for instead of a
+/// hard-coded median. Defaults to `MEDIAN` when the field is omitted.
+///
+/// Supported methods: `MEDIAN`, `AVG`, `SUM`, `MIN`, `MAX`, `COUNT`, `MODE`,
+/// and `TRIMMED_MEAN:<p>`, where `<p>` is the percentage (0-49) trimmed from
+/// each tail before averaging.
+fn parse_aggregation_method(raw: Option<&str>) -> Result<(u8, u8)> {
+    let raw = match raw {
+        Some(raw) => raw.trim().to_uppercase(),
+        None => return Ok((0, 0)),
+    };
+
+    if let Some(p) = raw.strip_prefix("TRIMMED_MEAN:") {
+        let p: u8 = p
+            .parse()
+            .map_err(|_| anyhow!("Invalid trimmed-mean percentage: {p}"))?;
+        if p > 49 {
+            return Err(anyhow!("Trimmed-mean percentage must be below 50, got {p}"));
+        }
+        return Ok((7, p));
+    }
+
+    let tag = match raw.as_str() {
+        "MEDIAN" => 0,
+        "AVG" => 1,
+        "SUM" => 2,
+        "MIN" => 3,
+        "MAX" => 4,
+        "COUNT" => 5,
+        "MODE" => 6,
+        other => return Err(anyhow!("Unsupported aggregation method: {other}")),
+    };
+    Ok((tag, 0))
 }
 
 struct Reserves {
     reserve0: U256,
     reserve1: U256,
+    block_timestamp_last: u32,
 }
 
 impl Reserves {
@@ -185,8 +488,8 @@ fn price_from_reserves(config: &PairConfig, token0: &str, reserves: &Reserves) -
     u256_to_u128(price_scaled)
 }
 
-fn get_reserves(pair: &str, block_number: Option<u64>) -> Result<Reserves> {
-    let reserves_result = rpc_call(pair, SELECTOR_GET_RESERVES, block_number)?;
+fn get_reserves(rpc_url: &str, pair: &str, block_number: Option<u64>) -> Result<Reserves> {
+    let reserves_result = rpc_call(rpc_url, pair, SELECTOR_GET_RESERVES, block_number)?;
     let reserves_bytes = hex_to_bytes(&reserves_result)
         .ok_or_else(|| anyhow!("Failed to parse reserves hex"))?;
     if reserves_bytes.len() < 96 {
@@ -195,10 +498,72 @@ fn get_reserves(pair: &str, block_number: Option<u64>) -> Result<Reserves> {
 
     let reserve0 = u256_from_be_slice(&reserves_bytes[0..32]);
     let reserve1 = u256_from_be_slice(&reserves_bytes[32..64]);
-    Ok(Reserves { reserve0, reserve1 })
+    let block_timestamp_last = u256_from_be_slice(&reserves_bytes[64..96]).low_u32();
+    Ok(Reserves {
+        reserve0,
+        reserve1,
+        block_timestamp_last,
+    })
 }
 
-fn rpc_call(to: &str, data: &str, block_number: Option<u64>) -> Result<String> {
+/// Reads the cumulative price accumulator (UQ112x112 fixed point) for the
+/// pair's base token, denominated in the quote token, at `block_number`.
+fn fetch_cumulative_price(
+    rpc_url: &str,
+    pair_config: &PairConfig,
+    token0: &str,
+    block_number: u64,
+) -> Result<U256> {
+    let selector = if token0.eq_ignore_ascii_case(pair_config.base) {
+        SELECTOR_PRICE0_CUMULATIVE
+    } else if token0.eq_ignore_ascii_case(pair_config.quote) {
+        SELECTOR_PRICE1_CUMULATIVE
+    } else {
+        return Err(anyhow!("token0 mismatch for pair"));
+    };
+
+    let result = rpc_call(rpc_url, pair_config.pair, selector, Some(block_number))?;
+    let bytes =
+        hex_to_bytes(&result).ok_or_else(|| anyhow!("Failed to parse cumulative price hex"))?;
+    Ok(u256_from_be_slice(&bytes))
+}
+
+/// Computes the time-weighted average price over `[timestamp_past,
+/// timestamp_now]` from the pair's cumulative price accumulators, rescaled
+/// out of UQ112x112 fixed point and into the `SCALE` representation used
+/// elsewhere. Falls back to `spot_fallback` if the window is degenerate
+/// (zero elapsed time, e.g. both snapshots landed in the same block).
+fn twap_price(
+    pair_config: &PairConfig,
+    cumulative_now: U256,
+    cumulative_past: U256,
+    timestamp_now: u32,
+    timestamp_past: u32,
+    spot_fallback: u128,
+) -> Result<u128> {
+    let elapsed = timestamp_now.wrapping_sub(timestamp_past);
+    if elapsed == 0 {
+        return Ok(spot_fallback);
+    }
+
+    // The accumulator is designed to wrap around u256, so the diff must be
+    // computed with wrapping subtraction rather than a checked one.
+    let (cumulative_diff, _) = cumulative_now.overflowing_sub(cumulative_past);
+
+    let numerator = cumulative_diff
+        .saturating_mul(pow10_u256(pair_config.base_decimals as u32))
+        .saturating_mul(U256::from(SCALE));
+    let denominator = (U256::one() << 112)
+        .saturating_mul(U256::from(elapsed))
+        .saturating_mul(pow10_u256(pair_config.quote_decimals as u32));
+    if denominator.is_zero() {
+        return Err(anyhow!("Degenerate TWAP denominator"));
+    }
+
+    u256_to_u128(numerator / denominator)
+}
+
+fn rpc_call(rpc_url: &str, to: &str, data: &str, block_number: Option<u64>) -> Result<String> {
     let block_tag = block_number
         .map(|number| format!("0x{number:x}"))
         .unwrap_or_else(|| "latest".to_string());
@@ -216,7 +581,7 @@ fn rpc_call(to: &str, data: &str, block_number: Option<u64>) -> Result<String> {
         ]
     });
 
-    let json_value = rpc_request(body)?;
+    let json_value = rpc_request(rpc_url, body)?;
     if let Some(error) = json_value.get("error") {
         return Err(anyhow!("RPC error: {error}"));
     }
@@ -228,7 +593,7 @@ fn rpc_call(to: &str, data: &str, block_number: Option<u64>) -> Result<String> {
     Ok(result.to_string())
 }
 
-fn rpc_request(body: serde_json::Value) -> Result<serde_json::Value> {
+fn rpc_request(rpc_url: &str, body: serde_json::Value) -> Result<serde_json::Value> {
     let body_bytes = serde_json::to_vec(&body)?;
     let mut headers = std::collections::BTreeMap::new();
     headers.insert("Content-Type".to_string(), "application/json".to_string());
@@ -240,7 +605,7 @@ fn rpc_request(body: serde_json::Value) -> Result<serde_json::Value> {
         timeout_ms: Some(10_000),
     };
 
-    let response = http_fetch(RPC_URL.to_string(), Some(options));
+    let response = http_fetch(rpc_url.to_string(), Some(options));
     if !response.is_ok() {
         elog!(
             "HTTP Response was rejected: {} - {}",
@@ -254,14 +619,14 @@ fn rpc_request(body: serde_json::Value) -> Result<serde_json::Value> {
     Ok(json_value)
 }
 
-fn rpc_get_block_number() -> Result<u64> {
+fn rpc_get_block_number(rpc_url: &str) -> Result<u64> {
     let body = json!({
         "jsonrpc": "2.0",
         "id": 1,
         "method": "eth_blockNumber",
         "params": []
     });
-    let json_value = rpc_request(body)?;
+    let json_value = rpc_request(rpc_url, body)?;
     let result = json_value
         .get("result")
         .and_then(|value| value.as_str())
@@ -313,6 +678,20 @@ fn u256_to_u128(value: U256) -> Result<u128> {
     Ok(value.as_u128())
 }
 
+/// Reconciles a per-endpoint output field by taking its median, so a
+/// single lying or stale RPC provider can't move the reported value on
+/// its own.
+fn median_u128(data: &[u128]) -> u128 {
+    let mut sorted = data.to_vec();
+    sorted.sort_unstable();
+    let m = sorted.len();
+    if m % 2 == 0 {
+        sorted[m / 2 - 1].midpoint(sorted[m / 2])
+    } else {
+        sorted[m / 2]
+    }
+}
+
 fn abs_diff_u128(a: u128, b: u128) -> u128 {
     if a >= b {
         a - b
@@ -1,7 +1,14 @@
 use anyhow::{Result, anyhow};
-use ethabi::{Token, ethereum_types::U256};
+use ethabi::{
+    Token,
+    ethereum_types::{U256, U512},
+};
 use seda_sdk_rs::{Process, elog, get_reveals, log};
 
+/// Minimum number of samples that must survive MAD outlier rejection for a
+/// field; below this the tally has no meaningful consensus left to report.
+const MIN_SURVIVORS_AFTER_FILTER: usize = 1;
+
 pub fn tally_phase() -> Result<()> {
     if let Err(err) = tally_phase_inner() {
         elog!("Tally error: {err}");
@@ -14,12 +21,16 @@ pub fn tally_phase() -> Result<()> {
 fn tally_phase_inner() -> Result<()> {
     let reveals = get_reveals()?;
     let mut revealed_values: Vec<Vec<U256>> = Vec::new();
+    let mut revealed_methods: Vec<AggregationMethod> = Vec::new();
+    let mut revealed_outlier_ks: Vec<u8> = Vec::new();
 
     for reveal in reveals {
-        let decoded = decode_values(&reveal.body.reveal);
+        let decoded = decode_reveal(&reveal.body.reveal);
         match decoded {
-            Ok(values) => {
-                log!("Received values: {:?}", values);
+            Ok((method, outlier_k, values)) => {
+                log!("Received values: {values:?} (method: {method:?}, outlier_k: {outlier_k})");
+                revealed_methods.push(method);
+                revealed_outlier_ks.push(outlier_k);
                 revealed_values.push(values);
             }
             Err(err) => {
@@ -32,28 +43,43 @@ fn tally_phase_inner() -> Result<()> {
         Process::error("No consensus among revealed results".as_bytes());
     }
 
-    let final_values = median_each_field(&revealed_values)?;
+    let method = majority_method(&revealed_methods);
+    let outlier_k = majority_outlier_k(&revealed_outlier_ks);
+    let final_values = aggregate_each_field(&revealed_values, method, outlier_k)?;
     let result = ethabi::encode(&[Token::Array(
-        final_values
-            .into_iter()
-            .map(Token::Int)
-            .collect(),
+        final_values.into_iter().map(Token::Int).collect(),
     )]);
     Process::success(&result);
 }
 
-fn decode_values(bytes: &[u8]) -> Result<Vec<U256>> {
+/// Every reveal is an `int256[]` of
+/// `[method_tag, method_param, outlier_k, field0..fieldN]` produced by
+/// `execution_phase`. The leading two fields select the aggregation
+/// function the tally should use (see [`AggregationMethod`]); `outlier_k`
+/// is the MAD multiplier used to reject outliers before aggregating (see
+/// [`filter_outliers`]); the rest are the usual per-endpoint-reconciled
+/// data fields. The field count isn't fixed: the `GAS-CRO` input mode
+/// reveals 2 fields (base fee, priority fee) while the WCRO/USDC pricing
+/// mode reveals 4, so it's derived from the array length here rather than
+/// asserted against a constant.
+fn decode_reveal(bytes: &[u8]) -> Result<(AggregationMethod, u8, Vec<U256>)> {
     let tokens = ethabi::decode(
-        &[ethabi::ParamType::Array(Box::new(ethabi::ParamType::Int(256)))],
+        &[ethabi::ParamType::Array(Box::new(ethabi::ParamType::Int(
+            256,
+        )))],
         bytes,
     )?;
     let array = match tokens.first() {
         Some(Token::Array(values)) => values,
         _ => return Err(anyhow!("Expected array token")),
     };
-    if array.len() != 4 {
-        return Err(anyhow!("Expected 4 values, got {}", array.len()));
+    if array.len() < 3 {
+        return Err(anyhow!(
+            "Expected at least 3 values (method_tag, method_param, outlier_k), got {}",
+            array.len()
+        ));
     }
+
     let mut values = Vec::with_capacity(array.len());
     for token in array {
         match token {
@@ -61,24 +87,165 @@ fn decode_values(bytes: &[u8]) -> Result<Vec<U256>> {
             _ => return Err(anyhow!("Expected int256 token")),
         }
     }
-    Ok(values)
+
+    let method = AggregationMethod::from_tag(values[0].low_u32() as u8, values[1].low_u32() as u8)?;
+    let outlier_k = values[2].low_u32() as u8;
+    Ok((method, outlier_k, values[3..].to_vec()))
+}
+
+/// Aggregation functions a data request can select between, in addition to
+/// the original plain median. Each operates field-wise over the reveals'
+/// `int256[]` columns and keeps the existing `int256[]` ABI output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AggregationMethod {
+    Median,
+    Avg,
+    Sum,
+    Min,
+    Max,
+    Count,
+    Mode,
+    /// Drops the lowest and highest `p`% of samples before averaging.
+    TrimmedMean(u8),
+}
+
+impl AggregationMethod {
+    fn from_tag(tag: u8, param: u8) -> Result<Self> {
+        Ok(match tag {
+            0 => AggregationMethod::Median,
+            1 => AggregationMethod::Avg,
+            2 => AggregationMethod::Sum,
+            3 => AggregationMethod::Min,
+            4 => AggregationMethod::Max,
+            5 => AggregationMethod::Count,
+            6 => AggregationMethod::Mode,
+            7 => {
+                if param > 49 {
+                    return Err(anyhow!(
+                        "Trimmed-mean percentage must be below 50, got {param}"
+                    ));
+                }
+                AggregationMethod::TrimmedMean(param)
+            }
+            other => return Err(anyhow!("Unknown aggregation method tag: {other}")),
+        })
+    }
+}
+
+/// Reveals should all agree on the requested method (they derive it
+/// deterministically from the same data request input), so a minority
+/// reporting a different tag is treated as misbehaving rather than as a
+/// tie-break signal.
+fn majority_method(methods: &[AggregationMethod]) -> AggregationMethod {
+    methods
+        .iter()
+        .copied()
+        .max_by_key(|candidate| methods.iter().filter(|m| *m == candidate).count())
+        .unwrap_or(AggregationMethod::Median)
+}
+
+/// Reveals should all agree on the requested `k` (they derive it
+/// deterministically from the same data request input), so a minority
+/// reporting a different value is treated as misbehaving rather than as a
+/// tie-break signal.
+fn majority_outlier_k(ks: &[u8]) -> u8 {
+    ks.iter()
+        .copied()
+        .max_by_key(|candidate| ks.iter().filter(|k| *k == candidate).count())
+        .unwrap_or(0)
 }
 
-fn median_each_field(values: &[Vec<U256>]) -> Result<Vec<U256>> {
+fn aggregate_each_field(
+    values: &[Vec<U256>],
+    method: AggregationMethod,
+    outlier_k: u8,
+) -> Result<Vec<U256>> {
     if values.is_empty() {
         return Err(anyhow!("No values to aggregate"));
     }
-    if !values.iter().all(|row| row.len() == 4) {
+    let field_count = values[0].len();
+    if !values.iter().all(|row| row.len() == field_count) {
         return Err(anyhow!("Mismatched value length in reveals"));
     }
 
-    let mut medians = Vec::with_capacity(4);
-    for idx in 0..4 {
-        let mut col: Vec<U256> = values.iter().map(|row| row[idx]).collect();
-        col.sort();
-        medians.push(median_sorted(&col));
+    let mut aggregated = Vec::with_capacity(field_count);
+    for idx in 0..field_count {
+        let col: Vec<U256> = values.iter().map(|row| row[idx]).collect();
+        let survivors = filter_outliers(&col, outlier_k, idx);
+        if survivors.len() < MIN_SURVIVORS_AFTER_FILTER {
+            return Err(anyhow!(
+                "Only {} of {} samples survived outlier rejection for field {idx}, need at least {MIN_SURVIVORS_AFTER_FILTER}",
+                survivors.len(),
+                col.len()
+            ));
+        }
+        aggregated.push(aggregate_column(&survivors, method)?);
+    }
+    Ok(aggregated)
+}
+
+/// Rejects samples more than `k` median-absolute-deviations away from the
+/// column's median, so a near-50% colluding minority can't drag the final
+/// aggregate off the honest cluster. `k == 0` disables the filter; a `MAD`
+/// of zero (the honest majority agrees exactly) also disables it, since
+/// there's no meaningful spread to measure outliers against.
+fn filter_outliers(col: &[U256], k: u8, field_idx: usize) -> Vec<U256> {
+    if k == 0 || col.len() < 3 {
+        return col.to_vec();
+    }
+
+    let mut sorted = col.to_vec();
+    sorted.sort();
+    let median = median_sorted(&sorted);
+
+    let mut deviations: Vec<U256> = col.iter().map(|value| abs_diff(*value, median)).collect();
+    deviations.sort();
+    let mad = median_sorted(&deviations);
+    if mad.is_zero() {
+        return col.to_vec();
+    }
+
+    let threshold = mad.saturating_mul(U256::from(k));
+    let survivors: Vec<U256> = col
+        .iter()
+        .filter(|value| abs_diff(**value, median) <= threshold)
+        .copied()
+        .collect();
+
+    let rejected = col.len() - survivors.len();
+    if rejected > 0 {
+        log!("Field {field_idx}: rejected {rejected} outlier sample(s) (k={k}, MAD={mad})");
+    }
+    survivors
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b { a - b } else { b - a }
+}
+
+fn aggregate_column(col: &[U256], method: AggregationMethod) -> Result<U256> {
+    match method {
+        AggregationMethod::Median => {
+            let mut sorted = col.to_vec();
+            sorted.sort();
+            Ok(median_sorted(&sorted))
+        }
+        AggregationMethod::Avg => avg(col),
+        AggregationMethod::Sum => sum(col),
+        AggregationMethod::Min => col
+            .iter()
+            .min()
+            .copied()
+            .ok_or_else(|| anyhow!("No values to aggregate")),
+        AggregationMethod::Max => col
+            .iter()
+            .max()
+            .copied()
+            .ok_or_else(|| anyhow!("No values to aggregate")),
+        AggregationMethod::Count => Ok(U256::from(col.len() as u64)),
+        AggregationMethod::Mode => Ok(mode(col)),
+        AggregationMethod::TrimmedMean(p) => trimmed_mean(col, p),
     }
-    Ok(medians)
 }
 
 fn median_sorted(values: &[U256]) -> U256 {
@@ -89,3 +256,70 @@ fn median_sorted(values: &[U256]) -> U256 {
         values[mid]
     }
 }
+
+/// Sums in `U512` so a column of large `U256` values can't silently wrap
+/// before the low 256 bits are returned.
+fn sum(col: &[U256]) -> Result<U256> {
+    let total = col
+        .iter()
+        .fold(U512::zero(), |acc, value| acc + u256_to_u512(*value));
+    u512_to_u256(total)
+}
+
+fn avg(col: &[U256]) -> Result<U256> {
+    let total = col
+        .iter()
+        .fold(U512::zero(), |acc, value| acc + u256_to_u512(*value));
+    let count = U512::from(col.len() as u64);
+    let rounded = (total + count / U512::from(2u8)) / count;
+    u512_to_u256(rounded)
+}
+
+/// Buckets equal values and returns the smallest most-frequent one.
+fn mode(col: &[U256]) -> U256 {
+    let mut sorted = col.to_vec();
+    sorted.sort();
+
+    let mut best_value = sorted[0];
+    let mut best_count = 0usize;
+    let mut idx = 0;
+    while idx < sorted.len() {
+        let value = sorted[idx];
+        let mut end = idx + 1;
+        while end < sorted.len() && sorted[end] == value {
+            end += 1;
+        }
+        if end - idx > best_count {
+            best_count = end - idx;
+            best_value = value;
+        }
+        idx = end;
+    }
+    best_value
+}
+
+fn trimmed_mean(col: &[U256], p: u8) -> Result<U256> {
+    let mut sorted = col.to_vec();
+    sorted.sort();
+    let n = sorted.len();
+    let trim = (n * p as usize) / 100;
+    if trim * 2 >= n {
+        return Err(anyhow!("Trimmed-mean percentage too large for {n} samples"));
+    }
+    avg(&sorted[trim..n - trim])
+}
+
+fn u256_to_u512(value: U256) -> U512 {
+    let mut bytes = [0u8; 64];
+    value.to_big_endian(&mut bytes[32..]);
+    U512::from_big_endian(&bytes)
+}
+
+fn u512_to_u256(value: U512) -> Result<U256> {
+    if value > u256_to_u512(U256::max_value()) {
+        return Err(anyhow!("Aggregated value exceeds int256 range"));
+    }
+    let mut bytes = [0u8; 64];
+    value.to_big_endian(&mut bytes);
+    Ok(U256::from_big_endian(&bytes[32..]))
+}
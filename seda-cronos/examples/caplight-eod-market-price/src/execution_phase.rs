@@ -14,22 +14,70 @@ const PROXY_PUBLIC_KEY: &str = "02088452cd5025f33d7ce95ee8eb7ba34b94b518ea23b189
 
 const ALLOWED_PITCHBOOK_IDS: [&str; 1] = ["54782-29"];
 
-fn parse_input_pair(input: &str) -> Result<String> {
+/// Default MAD multiplier for the tally phase's outlier rejection; see
+/// `parse_input_pair`.
+const DEFAULT_OUTLIER_K: u8 = 3;
+
+fn parse_input_pair(input: &str) -> Result<(String, u8, u8, u8)> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
         return Err(anyhow!("No input provided"));
     }
 
     if let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed) {
+        let (method_tag, method_param) =
+            parse_aggregation_method(value.get("method").and_then(|v| v.as_str()))?;
+        let outlier_k = value
+            .get("outlier_k")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u8)
+            .unwrap_or(DEFAULT_OUTLIER_K);
         if let Some(pair) = value.get("pair").and_then(|v| v.as_str()) {
-            return Ok(pair.to_string());
+            return Ok((pair.to_string(), method_tag, method_param, outlier_k));
         }
         if let Some(pair) = value.as_str() {
-            return Ok(pair.to_string());
+            return Ok((pair.to_string(), method_tag, method_param, outlier_k));
+        }
+    }
+
+    Ok((trimmed.to_string(), 0, 0, DEFAULT_OUTLIER_K))
+}
+
+/// Parses the optional `"method"` field of the data request input into the
+/// `(tag, param)` pair prepended to the reveal, so the tally phase can
+/// aggregate with the method the requester asked for instead of a
+/// hard-coded median. Defaults to `MEDIAN` when the field is omitted.
+///
+/// Supported methods: `MEDIAN`, `AVG`, `SUM`, `MIN`, `MAX`, `COUNT`, `MODE`,
+/// and `TRIMMED_MEAN:<p>`, where `<p>` is the percentage (0-49) trimmed from
+/// each tail before averaging.
+fn parse_aggregation_method(raw: Option<&str>) -> Result<(u8, u8)> {
+    let raw = match raw {
+        Some(raw) => raw.trim().to_uppercase(),
+        None => return Ok((0, 0)),
+    };
+
+    if let Some(p) = raw.strip_prefix("TRIMMED_MEAN:") {
+        let p: u8 = p
+            .parse()
+            .map_err(|_| anyhow!("Invalid trimmed-mean percentage: {p}"))?;
+        if p > 49 {
+            return Err(anyhow!("Trimmed-mean percentage must be below 50, got {p}"));
         }
+        return Ok((7, p));
     }
 
-    Ok(trimmed.to_string())
+    let tag = match raw.as_str() {
+        "MEDIAN" => 0,
+        "AVG" => 1,
+        "SUM" => 2,
+        "MIN" => 3,
+        "MAX" => 4,
+        "COUNT" => 5,
+        "MODE" => 6,
+        other => return Err(anyhow!("Unsupported aggregation method: {other}")),
+    };
+    Ok((tag, 0))
 }
 
 #[cfg(not(any(feature = "testnet", feature = "mainnet")))]
@@ -58,7 +106,7 @@ pub fn execution_phase() -> Result<()> {
     use seda_sdk_rs::{HttpFetchMethod, HttpFetchOptions};
 
     let dr_inputs_raw = String::from_utf8(Process::get_inputs())?;
-    let pitchbook_id = parse_input_pair(&dr_inputs_raw)?;
+    let (pitchbook_id, method_tag, method_param, outlier_k) = parse_input_pair(&dr_inputs_raw)?;
     if !ALLOWED_PITCHBOOK_IDS.contains(&pitchbook_id.as_str()) {
         elog!("Unsupported pitchbook id: {pitchbook_id}");
         Process::error("Unsupported pitchbook id".as_bytes());
@@ -102,8 +150,14 @@ pub fn execution_phase() -> Result<()> {
     let price_lossless = (price * 1_000_000.0) as u128;
     log!("Fetched price: {price_lossless:?}");
 
-    // Scaled 1e6 u128, serialized little-endian for tally.
-    Process::success(&price_lossless.to_le_bytes());
+    // Scaled 1e6 u128, serialized little-endian for tally, prefixed with
+    // the aggregation method tag, parameter, and outlier-rejection k byte.
+    let mut reveal = Vec::with_capacity(19);
+    reveal.push(method_tag);
+    reveal.push(method_param);
+    reveal.push(outlier_k);
+    reveal.extend_from_slice(&price_lossless.to_le_bytes());
+    Process::success(&reveal);
 
     Ok(())
 }
@@ -1,21 +1,28 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use ethabi::{Token, ethereum_types::U256};
 use seda_sdk_rs::{Process, elog, get_reveals, log};
 
+/// Minimum number of samples that must survive MAD outlier rejection; below
+/// this the tally has no meaningful consensus left to report.
+const MIN_SURVIVORS_AFTER_FILTER: usize = 1;
+
 pub fn tally_phase() -> Result<()> {
     let reveals = get_reveals()?;
     let mut revealed_prices = Vec::with_capacity(reveals.len());
+    let mut revealed_methods = Vec::with_capacity(reveals.len());
+    let mut revealed_outlier_ks = Vec::with_capacity(reveals.len());
 
     for reveal in reveals {
-        let price = match reveal.body.reveal.as_slice().try_into() {
-            Ok(price) => u128::from_le_bytes(price),
+        match decode_reveal(&reveal.body.reveal) {
+            Ok((method, outlier_k, price)) => {
+                revealed_methods.push(method);
+                revealed_outlier_ks.push(outlier_k);
+                revealed_prices.push(price);
+            }
             Err(err) => {
                 elog!("Failed to parse revealed prices: {err}");
-                continue;
             }
-        };
-
-        revealed_prices.push(price);
+        }
     }
 
     if revealed_prices.is_empty() {
@@ -23,9 +30,17 @@ pub fn tally_phase() -> Result<()> {
         return Ok(());
     }
 
-    let final_price = median(&revealed_prices);
+    let outlier_k = majority_outlier_k(&revealed_outlier_ks);
+    let survivors = filter_outliers(&revealed_prices, outlier_k);
+    if survivors.len() < MIN_SURVIVORS_AFTER_FILTER {
+        Process::error("No consensus among revealed results".as_bytes());
+        return Ok(());
+    }
+
+    let method = majority_method(&revealed_methods);
+    let final_price = aggregate(&survivors, method)?;
     let final_prices = Token::Array(vec![Token::Int(U256::from(final_price))]);
-    log!("Final median prices: {final_prices:?}");
+    log!("Final aggregated price ({method:?}): {final_prices:?}");
 
     // Output is int256[] with length 1; array is extensible.
     let result = ethabi::encode(&[final_prices]);
@@ -34,6 +49,136 @@ pub fn tally_phase() -> Result<()> {
     Ok(())
 }
 
+/// Every reveal is
+/// `[method_tag, method_param, outlier_k, price (u128 little-endian)]`
+/// produced by `execution_phase`. The leading two bytes select the
+/// aggregation function the tally should use (see [`AggregationMethod`]);
+/// `outlier_k` is the MAD multiplier used to reject outliers before
+/// aggregating (see [`filter_outliers`]).
+fn decode_reveal(bytes: &[u8]) -> Result<(AggregationMethod, u8, u128)> {
+    if bytes.len() != 19 {
+        return Err(anyhow!("Expected 19-byte reveal, got {}", bytes.len()));
+    }
+    let method = AggregationMethod::from_tag(bytes[0], bytes[1])?;
+    let outlier_k = bytes[2];
+    let price = u128::from_le_bytes(bytes[3..19].try_into().unwrap());
+    Ok((method, outlier_k, price))
+}
+
+/// Aggregation functions a data request can select between, in addition to
+/// the original plain median.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AggregationMethod {
+    Median,
+    Avg,
+    Sum,
+    Min,
+    Max,
+    Count,
+    Mode,
+    /// Drops the lowest and highest `p`% of samples before averaging.
+    TrimmedMean(u8),
+}
+
+impl AggregationMethod {
+    fn from_tag(tag: u8, param: u8) -> Result<Self> {
+        Ok(match tag {
+            0 => AggregationMethod::Median,
+            1 => AggregationMethod::Avg,
+            2 => AggregationMethod::Sum,
+            3 => AggregationMethod::Min,
+            4 => AggregationMethod::Max,
+            5 => AggregationMethod::Count,
+            6 => AggregationMethod::Mode,
+            7 => {
+                if param > 49 {
+                    return Err(anyhow!(
+                        "Trimmed-mean percentage must be below 50, got {param}"
+                    ));
+                }
+                AggregationMethod::TrimmedMean(param)
+            }
+            other => return Err(anyhow!("Unknown aggregation method tag: {other}")),
+        })
+    }
+}
+
+/// Reveals should all agree on the requested method (they derive it
+/// deterministically from the same data request input), so a minority
+/// reporting a different tag is treated as misbehaving rather than as a
+/// tie-break signal.
+fn majority_method(methods: &[AggregationMethod]) -> AggregationMethod {
+    methods
+        .iter()
+        .copied()
+        .max_by_key(|candidate| methods.iter().filter(|m| *m == candidate).count())
+        .unwrap_or(AggregationMethod::Median)
+}
+
+/// Reveals should all agree on the requested `k` (they derive it
+/// deterministically from the same data request input), so a minority
+/// reporting a different value is treated as misbehaving rather than as a
+/// tie-break signal.
+fn majority_outlier_k(ks: &[u8]) -> u8 {
+    ks.iter()
+        .copied()
+        .max_by_key(|candidate| ks.iter().filter(|k| *k == candidate).count())
+        .unwrap_or(0)
+}
+
+/// Rejects samples more than `k` median-absolute-deviations away from the
+/// median, so a near-50% colluding minority can't drag the final aggregate
+/// off the honest cluster. `k == 0` disables the filter; a `MAD` of zero
+/// (the honest majority agrees exactly) also disables it, since there's no
+/// meaningful spread to measure outliers against.
+fn filter_outliers(data: &[u128], k: u8) -> Vec<u128> {
+    if k == 0 || data.len() < 3 {
+        return data.to_vec();
+    }
+
+    let m = median(data);
+    let mut deviations: Vec<u128> = data.iter().map(|value| value.abs_diff(m)).collect();
+    deviations.sort_unstable();
+    let mad = median(&deviations);
+    if mad == 0 {
+        return data.to_vec();
+    }
+
+    let threshold = mad.saturating_mul(k as u128);
+    let survivors: Vec<u128> = data
+        .iter()
+        .copied()
+        .filter(|value| value.abs_diff(m) <= threshold)
+        .collect();
+
+    let rejected = data.len() - survivors.len();
+    if rejected > 0 {
+        log!("Rejected {rejected} outlier sample(s) (k={k}, MAD={mad})");
+    }
+    survivors
+}
+
+fn aggregate(data: &[u128], method: AggregationMethod) -> Result<u128> {
+    match method {
+        AggregationMethod::Median => Ok(median(data)),
+        AggregationMethod::Avg => avg(data),
+        AggregationMethod::Sum => sum(data),
+        AggregationMethod::Min => data
+            .iter()
+            .copied()
+            .min()
+            .ok_or_else(|| anyhow!("No values to aggregate")),
+        AggregationMethod::Max => data
+            .iter()
+            .copied()
+            .max()
+            .ok_or_else(|| anyhow!("No values to aggregate")),
+        AggregationMethod::Count => Ok(data.len() as u128),
+        AggregationMethod::Mode => Ok(mode(data)),
+        AggregationMethod::TrimmedMean(p) => trimmed_mean(data, p),
+    }
+}
+
 fn median(data: &[u128]) -> u128 {
     let m = data.len();
     if m == 0 {
@@ -51,3 +196,62 @@ fn median(data: &[u128]) -> u128 {
         sorted_data[m / 2]
     }
 }
+
+/// Accumulates in `U256` so a batch of large `u128` values can't silently
+/// wrap before the result is narrowed back down.
+fn sum(data: &[u128]) -> Result<u128> {
+    let total = data
+        .iter()
+        .fold(U256::zero(), |acc, value| acc + U256::from(*value));
+    u256_to_u128(total)
+}
+
+fn avg(data: &[u128]) -> Result<u128> {
+    let total = data
+        .iter()
+        .fold(U256::zero(), |acc, value| acc + U256::from(*value));
+    let count = U256::from(data.len() as u64);
+    let rounded = (total + count / U256::from(2u8)) / count;
+    u256_to_u128(rounded)
+}
+
+/// Buckets equal values and returns the smallest most-frequent one.
+fn mode(data: &[u128]) -> u128 {
+    let mut sorted = data.to_vec();
+    sorted.sort_unstable();
+
+    let mut best_value = sorted[0];
+    let mut best_count = 0usize;
+    let mut idx = 0;
+    while idx < sorted.len() {
+        let value = sorted[idx];
+        let mut end = idx + 1;
+        while end < sorted.len() && sorted[end] == value {
+            end += 1;
+        }
+        if end - idx > best_count {
+            best_count = end - idx;
+            best_value = value;
+        }
+        idx = end;
+    }
+    best_value
+}
+
+fn trimmed_mean(data: &[u128], p: u8) -> Result<u128> {
+    let mut sorted = data.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len();
+    let trim = (n * p as usize) / 100;
+    if trim * 2 >= n {
+        return Err(anyhow!("Trimmed-mean percentage too large for {n} samples"));
+    }
+    avg(&sorted[trim..n - trim])
+}
+
+fn u256_to_u128(value: U256) -> Result<u128> {
+    if value > U256::from(u128::MAX) {
+        return Err(anyhow!("Aggregated value exceeds u128 range"));
+    }
+    Ok(value.as_u128())
+}
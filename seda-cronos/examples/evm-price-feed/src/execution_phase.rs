@@ -9,14 +9,27 @@ use seda_sdk_rs::{
     bytes::ToBytes,
 };
 use serde_json::json;
+use sha3::{Digest, Keccak256};
 
 const DEFAULT_PAIR: &str = "WCRO-USDC";
-const RPC_URL: &str =
-    "https://mainnet-sticky.cronoslabs.com/v1/d3642384d334ff6ff1c4baebfdf3ef7d";
+
+/// Independent Cronos RPC endpoints queried for every price computation.
+/// No single provider is trusted: at least [`RPC_QUORUM`] of them must
+/// return a `price_scaled` (each computed independently), and the reported
+/// price is the median of those values.
+const RPC_URLS: &[&str] = &[
+    "https://mainnet-sticky.cronoslabs.com/v1/d3642384d334ff6ff1c4baebfdf3ef7d",
+    "https://cronos.blockpi.network/v1/rpc/0467a344ecda6f87cc7118bd02a14f5818a2f5ff",
+    "https://evm.cronos.org",
+];
+const RPC_QUORUM: usize = 2;
 
 const SELECTOR_GET_RESERVES: &str = "0902f1ac";
 const SELECTOR_SLOT0: &str = "3850c7bd";
 const SELECTOR_TOKEN0: &str = "0dfe1681";
+const SELECTOR_OBSERVE: &str = "883bdbfd";
+
+const DEFAULT_TWAP_WINDOW_SECS: u32 = 1_800;
 
 const WCRO_ADDRESS: &str = "0x5C7F8A570d578ED84E63fdFA7b1eE72dEae1AE23";
 const USDC_ADDRESS: &str = "0xc21223249CA28397B4B6541dfFaEcC539BfF0c59";
@@ -31,22 +44,103 @@ const WBTC_WCRO_PAIR: &str = "0x8F09fff247B8FDb80461E5cf5E82dD1AE2ebd6d7";
 const WCRO_ETH_PAIR: &str = "0xA111C17F8b8303280d3EB01BbCd61000AA7f39f9";
 const USDT_USDC_V3_POOL: &str = "0x0438a75009519f6284fa9e050e54d940302b2e93";
 
-fn parse_input_pair(input: &str) -> Result<String> {
+// UniswapV2Pair storage layout: token0 lives at slot 6, and reserve0/reserve1/
+// blockTimestampLast are packed into slot 8.
+const TOKEN0_SLOT: u64 = 6;
+const RESERVES_SLOT: u64 = 8;
+
+/// Which pool a request resolves to: one of the built-in named pairs, or an
+/// arbitrary caller-supplied pool with on-chain decimals discovery.
+enum Target {
+    Named(String),
+    Custom(CustomPool),
+}
+
+/// A caller-supplied `{ "pool", "base", "quote", "version" }` request. Unlike
+/// the named pairs, decimals aren't trusted from the caller: they're looked
+/// up on-chain via `decimals()` in [`resolve_custom_pair_config`].
+struct CustomPool {
+    pool: String,
+    base: String,
+    quote: String,
+    version: String,
+}
+
+struct ParsedInput {
+    target: Target,
+    verify: bool,
+    /// TWAP window in seconds for V3 pools; `None` means use the
+    /// instantaneous `slot0` price instead of `observe()`.
+    twap_window: Option<u32>,
+}
+
+fn parse_input(input: &str) -> Result<ParsedInput> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
-        return Ok(DEFAULT_PAIR.to_string());
+        return Ok(ParsedInput {
+            target: Target::Named(DEFAULT_PAIR.to_string()),
+            verify: false,
+            twap_window: None,
+        });
     }
 
     if let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed) {
+        let verify = value
+            .get("verify")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let twap_window = match value.get("twapWindow").and_then(|v| v.as_u64()) {
+            Some(window) => Some(window as u32),
+            None if value.get("twap").and_then(|v| v.as_bool()).unwrap_or(false) => {
+                Some(DEFAULT_TWAP_WINDOW_SECS)
+            }
+            None => None,
+        };
+        if let Some(pool) = value.get("pool").and_then(|v| v.as_str()) {
+            let base = value
+                .get("base")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("Custom pool request missing \"base\""))?;
+            let quote = value
+                .get("quote")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("Custom pool request missing \"quote\""))?;
+            let version = value
+                .get("version")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("Custom pool request missing \"version\""))?;
+            return Ok(ParsedInput {
+                target: Target::Custom(CustomPool {
+                    pool: pool.to_string(),
+                    base: base.to_string(),
+                    quote: quote.to_string(),
+                    version: version.to_lowercase(),
+                }),
+                verify,
+                twap_window,
+            });
+        }
         if let Some(pair) = value.get("pair").and_then(|v| v.as_str()) {
-            return Ok(pair.to_string());
+            return Ok(ParsedInput {
+                target: Target::Named(pair.to_string()),
+                verify,
+                twap_window,
+            });
         }
         if let Some(pair) = value.as_str() {
-            return Ok(pair.to_string());
+            return Ok(ParsedInput {
+                target: Target::Named(pair.to_string()),
+                verify,
+                twap_window,
+            });
         }
     }
 
-    Ok(trimmed.to_string())
+    Ok(ParsedInput {
+        target: Target::Named(trimmed.to_string()),
+        verify: false,
+        twap_window: None,
+    })
 }
 
 fn validate_pair(pair: &str) -> Result<String> {
@@ -57,54 +151,76 @@ fn validate_pair(pair: &str) -> Result<String> {
     }
 }
 
+/// Pool addresses callers may query via a custom `{ "pool", ... }` request,
+/// in addition to the named pairs above. Empty means any pool is accepted:
+/// tokens and decimals are always resolved on-chain, never trusted from the
+/// caller, so gating is only needed to restrict which pools get queried at
+/// all.
+const CUSTOM_POOL_ALLOWLIST: &[&str] = &[];
+
+fn validate_custom_pool(custom: &CustomPool) -> Result<()> {
+    if !CUSTOM_POOL_ALLOWLIST.is_empty()
+        && !CUSTOM_POOL_ALLOWLIST
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&custom.pool))
+    {
+        return Err(anyhow!("Pool {} is not in the allowlist", custom.pool));
+    }
+    match custom.version.as_str() {
+        "v2" | "v3" => Ok(()),
+        other => Err(anyhow!("Unsupported pool version: {other}")),
+    }
+}
+
 pub fn execution_phase() -> Result<()> {
     let input = String::from_utf8(Process::get_inputs())?;
-    let pair = validate_pair(&parse_input_pair(&input)?)?;
-
-    let pair_config = match pair.as_str() {
-        "WCRO-USDC" => PairConfig::v2(
-            WCRO_USDC_PAIR,
-            WCRO_ADDRESS,
-            USDC_ADDRESS,
-            18,
-            6,
-        ),
-        "VVS-WCRO" => PairConfig::v2(
-            VVS_WCRO_PAIR,
-            VVS_ADDRESS,
-            WCRO_ADDRESS,
-            18,
-            18,
-        ),
-        "WBTC-WCRO" => PairConfig::v2(
-            WBTC_WCRO_PAIR,
-            WBTC_ADDRESS,
-            WCRO_ADDRESS,
-            8,
-            18,
-        ),
-        "WCRO-ETH" => PairConfig::v2(
-            WCRO_ETH_PAIR,
-            WCRO_ADDRESS,
-            WETH_ADDRESS,
-            18,
-            18,
-        ),
-        "USDT-USDC" => PairConfig::v3(
-            USDT_USDC_V3_POOL,
-            USDT_ADDRESS,
-            USDC_ADDRESS,
-            6,
-            6,
-        ),
-        _ => return Err(anyhow!("Unsupported pair: {pair}")),
-    };
+    let parsed = parse_input(&input)?;
 
-    let price_scaled = match pair_config {
-        PairConfig::V2(config) => price_from_v2(&config)?,
-        PairConfig::V3(config) => price_from_v3(&config)?,
+    let (label, pair_config) = match &parsed.target {
+        Target::Named(pair) => {
+            let pair = validate_pair(pair)?;
+            let config = named_pair_config(&pair)?;
+            (pair, config)
+        }
+        Target::Custom(custom) => {
+            validate_custom_pool(custom)?;
+            let config = resolve_custom_pair_config(custom)?;
+            (custom.pool.clone(), config)
+        }
     };
 
+    let mut prices = Vec::with_capacity(RPC_URLS.len());
+    for &rpc_url in RPC_URLS {
+        let result = match &pair_config {
+            PairConfig::V2(config) if parsed.verify => {
+                log!("Verifying {label} reserves via eth_getProof against stateRoot ({rpc_url})");
+                price_from_v2_verified(rpc_url, config)
+            }
+            PairConfig::V2(config) => price_from_v2(rpc_url, config),
+            PairConfig::V3(config) => match parsed.twap_window {
+                Some(window) => {
+                    log!("Computing {label} TWAP over a {window}s window via observe() ({rpc_url})");
+                    price_from_v3_twap(rpc_url, config, window)
+                }
+                None => price_from_v3(rpc_url, config),
+            },
+        };
+
+        match result {
+            Ok(price) => prices.push(price),
+            Err(err) => elog!("RPC endpoint {rpc_url} failed: {err}"),
+        }
+    }
+
+    if prices.len() < RPC_QUORUM {
+        return Err(anyhow!(
+            "Only {} of {} RPC endpoints returned a price, need quorum of {RPC_QUORUM}",
+            prices.len(),
+            RPC_URLS.len()
+        ));
+    }
+
+    let price_scaled = median_u128(&prices);
     log!("Computed price (scaled 1e6): {price_scaled}");
     // Scaled 1e6 u128, serialized little-endian for tally.
     Process::success(&price_scaled.to_le_bytes());
@@ -113,17 +229,17 @@ pub fn execution_phase() -> Result<()> {
 }
 
 struct V2Config {
-    pair: &'static str,
-    base: &'static str,
-    quote: &'static str,
+    pair: String,
+    base: String,
+    quote: String,
     base_decimals: u8,
     quote_decimals: u8,
 }
 
 struct V3Config {
-    pool: &'static str,
-    base: &'static str,
-    quote: &'static str,
+    pool: String,
+    base: String,
+    quote: String,
     base_decimals: u8,
     quote_decimals: u8,
 }
@@ -135,53 +251,182 @@ enum PairConfig {
 
 impl PairConfig {
     fn v2(
-        pair: &'static str,
-        base: &'static str,
-        quote: &'static str,
+        pair: impl Into<String>,
+        base: impl Into<String>,
+        quote: impl Into<String>,
         base_decimals: u8,
         quote_decimals: u8,
     ) -> Self {
         PairConfig::V2(V2Config {
-            pair,
-            base,
-            quote,
+            pair: pair.into(),
+            base: base.into(),
+            quote: quote.into(),
             base_decimals,
             quote_decimals,
         })
     }
 
     fn v3(
-        pool: &'static str,
-        base: &'static str,
-        quote: &'static str,
+        pool: impl Into<String>,
+        base: impl Into<String>,
+        quote: impl Into<String>,
         base_decimals: u8,
         quote_decimals: u8,
     ) -> Self {
         PairConfig::V3(V3Config {
-            pool,
-            base,
-            quote,
+            pool: pool.into(),
+            base: base.into(),
+            quote: quote.into(),
             base_decimals,
             quote_decimals,
         })
     }
 }
 
-fn price_from_v2(config: &V2Config) -> Result<u128> {
-    let reserves_result = rpc_call(config.pair, SELECTOR_GET_RESERVES)?;
-    let reserves_bytes = hex_to_bytes(&reserves_result)
+fn named_pair_config(pair: &str) -> Result<PairConfig> {
+    Ok(match pair {
+        "WCRO-USDC" => PairConfig::v2(WCRO_USDC_PAIR, WCRO_ADDRESS, USDC_ADDRESS, 18, 6),
+        "VVS-WCRO" => PairConfig::v2(VVS_WCRO_PAIR, VVS_ADDRESS, WCRO_ADDRESS, 18, 18),
+        "WBTC-WCRO" => PairConfig::v2(WBTC_WCRO_PAIR, WBTC_ADDRESS, WCRO_ADDRESS, 8, 18),
+        "WCRO-ETH" => PairConfig::v2(WCRO_ETH_PAIR, WCRO_ADDRESS, WETH_ADDRESS, 18, 18),
+        "USDT-USDC" => PairConfig::v3(USDT_USDC_V3_POOL, USDT_ADDRESS, USDC_ADDRESS, 6, 6),
+        _ => return Err(anyhow!("Unsupported pair: {pair}")),
+    })
+}
+
+/// Resolves a caller-supplied `{ pool, base, quote, version }` request into
+/// a [`PairConfig`], looking up each token's decimals on-chain via
+/// `decimals()` instead of trusting a hardcoded table like the named pairs
+/// above.
+fn resolve_custom_pair_config(custom: &CustomPool) -> Result<PairConfig> {
+    let base_decimals = fetch_decimals_quorum(&custom.base)?;
+    let quote_decimals = fetch_decimals_quorum(&custom.quote)?;
+
+    Ok(match custom.version.as_str() {
+        "v2" => PairConfig::v2(
+            custom.pool.clone(),
+            custom.base.clone(),
+            custom.quote.clone(),
+            base_decimals,
+            quote_decimals,
+        ),
+        "v3" => PairConfig::v3(
+            custom.pool.clone(),
+            custom.base.clone(),
+            custom.quote.clone(),
+            base_decimals,
+            quote_decimals,
+        ),
+        other => return Err(anyhow!("Unsupported pool version: {other}")),
+    })
+}
+
+const SELECTOR_DECIMALS: &str = "313ce567";
+
+/// Queries `decimals()` on every RPC endpoint and requires at least
+/// [`RPC_QUORUM`] of them to agree on the exact same value before trusting
+/// it. Custom pools (unlike the hardcoded named pairs above) are the
+/// untrusted surface this resolves, and `decimals()` directly scales the
+/// reported price by a power of ten — a single compromised endpoint lying
+/// here is just as dangerous as one lying about a price, so this gets the
+/// same quorum treatment as [`median_u128`]-reconciled fields rather than
+/// trusting whichever endpoint answers first.
+fn fetch_decimals_quorum(token: &str) -> Result<u8> {
+    let mut results = Vec::with_capacity(RPC_URLS.len());
+    for &rpc_url in RPC_URLS {
+        match fetch_decimals(rpc_url, token) {
+            Ok(decimals) => results.push(decimals),
+            Err(err) => elog!("RPC endpoint {rpc_url} failed to resolve decimals for {token}: {err}"),
+        }
+    }
+
+    let agreed = results
+        .iter()
+        .copied()
+        .max_by_key(|candidate| results.iter().filter(|d| *d == candidate).count())
+        .ok_or_else(|| anyhow!("No RPC endpoint returned decimals for {token}"))?;
+    let agreeing = results.iter().filter(|d| **d == agreed).count();
+    if agreeing < RPC_QUORUM {
+        return Err(anyhow!(
+            "Only {agreeing} of {} RPC endpoints agreed on decimals for {token}, need quorum of {RPC_QUORUM}",
+            results.len()
+        ));
+    }
+
+    Ok(agreed)
+}
+
+fn fetch_decimals(rpc_url: &str, token: &str) -> Result<u8> {
+    let result = rpc_call(rpc_url, token, SELECTOR_DECIMALS)?;
+    let bytes =
+        hex_to_bytes(&result).ok_or_else(|| anyhow!("Failed to parse decimals() hex"))?;
+    if bytes.len() < 32 {
+        return Err(anyhow!("decimals() result too short"));
+    }
+    let value = u256_to_u128(u256_from_be_slice(&bytes[bytes.len() - 32..]))?;
+    u8::try_from(value).map_err(|_| anyhow!("decimals() value out of range: {value}"))
+}
+
+fn price_from_v2(rpc_url: &str, config: &V2Config) -> Result<u128> {
+    let results = multicall(
+        rpc_url,
+        &[
+            (config.pair.as_str(), SELECTOR_GET_RESERVES),
+            (config.pair.as_str(), SELECTOR_TOKEN0),
+        ],
+    )?;
+
+    let reserves_bytes = hex_to_bytes(&results[0])
         .ok_or_else(|| anyhow!("Failed to parse reserves hex"))?;
     if reserves_bytes.len() < 64 {
         return Err(anyhow!("Reserves result too short"));
     }
-
     let reserve0 = u256_from_be_slice(&reserves_bytes[0..32]);
     let reserve1 = u256_from_be_slice(&reserves_bytes[32..64]);
 
-    let token0_result = rpc_call(config.pair, SELECTOR_TOKEN0)?;
-    let token0 = parse_address_from_32byte(&token0_result)
+    let token0 = parse_address_from_32byte(&results[1])
         .ok_or_else(|| anyhow!("Failed to parse token0 address"))?;
 
+    price_from_v2_reserves(config, &token0, reserve0, reserve1)
+}
+
+/// Trustless counterpart to [`price_from_v2`]: instead of trusting whatever
+/// `eth_call` returns, fetches an `eth_getProof` for the pair's `token0` and
+/// packed-reserves storage slots and verifies each value against the latest
+/// block's `stateRoot` before computing a price.
+fn price_from_v2_verified(rpc_url: &str, config: &V2Config) -> Result<u128> {
+    let (block_hash, state_root) = rpc_get_block_header(rpc_url)?;
+    let slots = [U256::from(TOKEN0_SLOT), U256::from(RESERVES_SLOT)];
+    let proof = rpc_get_proof(rpc_url, config.pair.as_str(), &slots, &block_hash)?;
+
+    let account_leaf = verify_account_proof(config.pair.as_str(), &proof.account_proof, state_root)?;
+    let storage_root = account_storage_root(&account_leaf)?;
+
+    let token0_value = verify_storage_slot(
+        U256::from(TOKEN0_SLOT),
+        &proof,
+        storage_root,
+    )?;
+    let token0 = format!("0x{:040x}", token0_value);
+
+    let reserves_value = verify_storage_slot(
+        U256::from(RESERVES_SLOT),
+        &proof,
+        storage_root,
+    )?;
+    let mask_112 = (U256::one() << 112) - U256::one();
+    let reserve0 = reserves_value & mask_112;
+    let reserve1 = (reserves_value >> 112) & mask_112;
+
+    price_from_v2_reserves(config, &token0, reserve0, reserve1)
+}
+
+fn price_from_v2_reserves(
+    config: &V2Config,
+    token0: &str,
+    reserve0: U256,
+    reserve1: U256,
+) -> Result<u128> {
     let base = config.base.to_lowercase();
     let quote = config.quote.to_lowercase();
 
@@ -204,22 +449,27 @@ fn price_from_v2(config: &V2Config) -> Result<u128> {
     let denominator = base_reserve.saturating_mul(quote_scale);
     let price_scaled = numerator / denominator;
 
-    Ok(u256_to_u128(price_scaled)?)
+    u256_to_u128(price_scaled)
 }
 
-fn price_from_v3(config: &V3Config) -> Result<u128> {
-    let slot0_result = rpc_call(config.pool, SELECTOR_SLOT0)?;
-    let slot0_bytes = hex_to_bytes(&slot0_result)
+fn price_from_v3(rpc_url: &str, config: &V3Config) -> Result<u128> {
+    let results = multicall(
+        rpc_url,
+        &[
+            (config.pool.as_str(), SELECTOR_SLOT0),
+            (config.pool.as_str(), SELECTOR_TOKEN0),
+        ],
+    )?;
+
+    let slot0_bytes = hex_to_bytes(&results[0])
         .ok_or_else(|| anyhow!("Failed to parse slot0 hex"))?;
     if slot0_bytes.len() < 32 {
         return Err(anyhow!("slot0 result too short"));
     }
-
     let sqrt_price_x96 = u256_from_be_slice(&slot0_bytes[0..32]);
     let price_x192 = sqrt_price_x96.saturating_mul(sqrt_price_x96);
 
-    let token0_result = rpc_call(config.pool, SELECTOR_TOKEN0)?;
-    let token0 = parse_address_from_32byte(&token0_result)
+    let token0 = parse_address_from_32byte(&results[1])
         .ok_or_else(|| anyhow!("Failed to parse token0 address"))?;
 
     let base = config.base.to_lowercase();
@@ -254,7 +504,190 @@ fn price_from_v3(config: &V3Config) -> Result<u128> {
     Ok(u256_to_u128(price_scaled)?)
 }
 
-fn rpc_call(to: &str, data: &str) -> Result<String> {
+/// Manipulation-resistant counterpart to [`price_from_v3`]: reads the pool's
+/// built-in `observe()` TWAP oracle over `window` seconds instead of the
+/// instantaneous `slot0` price.
+fn price_from_v3_twap(rpc_url: &str, config: &V3Config, window: u32) -> Result<u128> {
+    let observe_data = encode_observe_call(window);
+    let results = multicall(
+        rpc_url,
+        &[
+            (config.pool.as_str(), &observe_data),
+            (config.pool.as_str(), SELECTOR_TOKEN0),
+        ],
+    )?;
+
+    let observe_bytes = hex_to_bytes(&results[0])
+        .ok_or_else(|| anyhow!("Failed to parse observe() hex"))?;
+    // secondsAgo = [window, 0], so index 0 is `window` seconds ago and
+    // index 1 is now.
+    let tick_cumulative_past = decode_tick_cumulative(&observe_bytes, 0)?;
+    let tick_cumulative_now = decode_tick_cumulative(&observe_bytes, 1)?;
+
+    let tick = floor_div(
+        tick_cumulative_now - tick_cumulative_past,
+        window as i128,
+    );
+
+    let sqrt_price_x96 = sqrt_ratio_at_tick(tick)?;
+    let price_x192 = sqrt_price_x96.saturating_mul(sqrt_price_x96);
+
+    let token0 = parse_address_from_32byte(&results[1])
+        .ok_or_else(|| anyhow!("Failed to parse token0 address"))?;
+
+    let base = config.base.to_lowercase();
+    let quote = config.quote.to_lowercase();
+    let q192 = U256::one() << 192;
+
+    let price_scaled = if token0.eq_ignore_ascii_case(&base) {
+        let numerator = price_x192
+            .saturating_mul(pow10_u256(6))
+            .saturating_mul(pow10_u256(config.base_decimals as u32));
+        let denominator = q192.saturating_mul(pow10_u256(config.quote_decimals as u32));
+        numerator / denominator
+    } else if token0.eq_ignore_ascii_case(&quote) {
+        if price_x192.is_zero() {
+            return Err(anyhow!("Price is zero"));
+        }
+        let numerator = pow10_u256(6)
+            .saturating_mul(pow10_u256(config.quote_decimals as u32))
+            .saturating_mul(q192);
+        let denominator = pow10_u256(config.base_decimals as u32)
+            .saturating_mul(price_x192);
+        numerator / denominator
+    } else {
+        return Err(anyhow!("token0 mismatch for pool"));
+    };
+
+    u256_to_u128(price_scaled)
+}
+
+/// ABI-encodes `observe(uint32[])` with `[window, 0]` as the two "seconds
+/// ago" lookback points.
+fn encode_observe_call(window: u32) -> String {
+    format!(
+        "{SELECTOR_OBSERVE}{:064x}{:064x}{:064x}{:064x}",
+        0x20, // offset to the dynamic array
+        2,    // array length
+        window,
+        0u32,
+    )
+}
+
+/// Decodes the `idx`-th `int56` entry of the `tickCumulatives` array from an
+/// `observe()` return value, sign-extended to `i128`.
+fn decode_tick_cumulative(bytes: &[u8], idx: usize) -> Result<i128> {
+    if bytes.len() < 64 {
+        return Err(anyhow!("observe() result too short"));
+    }
+    let array_offset = u256_to_usize(u256_from_be_slice(&bytes[0..32]))?;
+    let length_start = array_offset;
+    let length = u256_to_usize(u256_from_be_slice(
+        bytes
+            .get(length_start..length_start + 32)
+            .ok_or_else(|| anyhow!("observe() result truncated (length)"))?,
+    ))?;
+    if idx >= length {
+        return Err(anyhow!("tickCumulatives index {idx} out of range"));
+    }
+
+    let elem_start = length_start + 32 + idx * 32;
+    let elem = bytes
+        .get(elem_start..elem_start + 32)
+        .ok_or_else(|| anyhow!("observe() result truncated (element)"))?;
+    Ok(u256_to_signed_i128(u256_from_be_slice(elem)))
+}
+
+/// Interprets a 256-bit two's-complement value as a signed integer. Only
+/// valid for values whose magnitude fits in `i128`, which holds for any
+/// realistic `int56` tick-cumulative.
+fn u256_to_signed_i128(value: U256) -> i128 {
+    if value.bit(255) {
+        let magnitude = (U256::MAX - value) + U256::one();
+        -(magnitude.as_u128() as i128)
+    } else {
+        value.as_u128() as i128
+    }
+}
+
+fn u256_to_usize(value: U256) -> Result<usize> {
+    if value > U256::from(u32::MAX) {
+        return Err(anyhow!("Value exceeds expected offset/length range"));
+    }
+    Ok(value.as_u64() as usize)
+}
+
+/// Floors `a / b` toward negative infinity (Rust's `/` truncates toward
+/// zero), matching Solidity's `arithmeticMeanTick` rounding rule.
+fn floor_div(a: i128, b: i128) -> i128 {
+    let quotient = a / b;
+    let remainder = a % b;
+    if remainder != 0 && (remainder < 0) != (b < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+/// Uniswap V3's `TickMath.getSqrtRatioAtTick`: computes `sqrtPriceX96 =
+/// 1.0001^(tick/2) * 2^96` via a per-bit multiplication table over the 20
+/// bits of `abs(tick)`, inverting the Q128.128 ratio for negative ticks.
+const TICK_MATH_FACTORS: [&str; 20] = [
+    "fffcb933bd6fad37aa2d162d1a594001",
+    "fff97272373d413259a46990580e213a",
+    "fff2e50f5f656932ef12357cf3c7fdcc",
+    "ffe5caca7e10e4e61c3624eaa0941cd0",
+    "ffcb9843d60f6159c9db58835c926644",
+    "ff973b41fa98c081472e6896dfb254c0",
+    "ff2ea16466c96a3843ec78b326b52861",
+    "fe5dee046a99a2a811c461f1969c3053",
+    "fcbe86c7900a88aedcffc83b479aa3a4",
+    "f987a7253ac413176f2b074cf7815e54",
+    "f3392b0822b70005940c7a398e4b70f3",
+    "e7159475a2c29b7443b29c7fa6e889d9",
+    "d097f3bdfd2022b8845ad8f792aa5825",
+    "a9f746462d870fdf8a65dc1f90e061e5",
+    "70d869a156d2a1b890bb3df62baf32f7",
+    "31be135f97d08fd981231505542fcfa6",
+    "09aa508b5b7a84e1c677de54f3e99bc9",
+    "5d6af8dedb81196699c329225ee604",
+    "2216e584f5fa1ea926041bedfe98",
+    "48a170391f7dc42444e8fa2",
+];
+
+fn sqrt_ratio_at_tick(tick: i128) -> Result<U256> {
+    const MAX_TICK: i128 = 887_272;
+    if tick.unsigned_abs() > MAX_TICK as u128 {
+        return Err(anyhow!("tick {tick} out of range"));
+    }
+    let abs_tick = tick.unsigned_abs() as u32;
+
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U256::from_str_radix(TICK_MATH_FACTORS[0], 16)?
+    } else {
+        U256::one() << 128
+    };
+    for (i, factor) in TICK_MATH_FACTORS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << i) != 0 {
+            ratio = (ratio * U256::from_str_radix(factor, 16)?) >> 128;
+        }
+    }
+
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Downshift from Q128.128 to Q64.96, rounding up.
+    let remainder = ratio % (U256::one() << 32);
+    let sqrt_price_x96 = ratio >> 32;
+    Ok(if remainder.is_zero() {
+        sqrt_price_x96
+    } else {
+        sqrt_price_x96 + U256::one()
+    })
+}
+
+fn rpc_call(rpc_url: &str, to: &str, data: &str) -> Result<String> {
     let body = json!({
         "jsonrpc": "2.0",
         "id": 1,
@@ -268,6 +701,173 @@ fn rpc_call(to: &str, data: &str) -> Result<String> {
         ]
     });
 
+    let json_value = rpc_request(rpc_url, body)?;
+    let result = json_value
+        .get("result")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| anyhow!("RPC response missing result"))?;
+
+    Ok(result.to_string())
+}
+
+const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+const SELECTOR_AGGREGATE3: &str = "82ad56cb";
+
+/// Batches `calls` into a single `aggregate3()` round-trip against
+/// Multicall3 instead of issuing one `eth_call` per `(to, data)` pair.
+/// Falls back to sequential `rpc_call`s if the multicall itself fails or a
+/// sub-call reverts, so chains without Multicall3 deployed still work.
+fn multicall(rpc_url: &str, calls: &[(&str, &str)]) -> Result<Vec<String>> {
+    match multicall_inner(rpc_url, calls) {
+        Ok(results) => Ok(results),
+        Err(err) => {
+            elog!("Multicall3 aggregate3 failed, falling back to sequential eth_call: {err}");
+            calls
+                .iter()
+                .map(|(to, data)| rpc_call(rpc_url, to, data))
+                .collect()
+        }
+    }
+}
+
+fn multicall_inner(rpc_url: &str, calls: &[(&str, &str)]) -> Result<Vec<String>> {
+    let data = encode_aggregate3(calls)?;
+    let result_hex = rpc_call(rpc_url, MULTICALL3_ADDRESS, &data)?;
+    let result_bytes =
+        hex_to_bytes(&result_hex).ok_or_else(|| anyhow!("Failed to parse aggregate3 hex"))?;
+    let results = decode_aggregate3_result(&result_bytes)?;
+    if results.len() != calls.len() {
+        return Err(anyhow!("aggregate3 returned {} results, expected {}", results.len(), calls.len()));
+    }
+
+    results
+        .into_iter()
+        .zip(calls)
+        .map(|((success, return_data), (to, _))| {
+            if !success {
+                return Err(anyhow!("aggregate3 sub-call to {to} reverted"));
+            }
+            Ok(format!("0x{}", hex_encode(&return_data)))
+        })
+        .collect()
+}
+
+/// ABI-encodes `aggregate3((address,bool,bytes)[])` for `calls`, marking
+/// every sub-call as `allowFailure = true` so one bad RPC target doesn't
+/// revert the whole batch.
+fn encode_aggregate3(calls: &[(&str, &str)]) -> Result<String> {
+    let mut tuples = Vec::with_capacity(calls.len());
+    for (to, data) in calls {
+        let call_data = hex_to_bytes(data).ok_or_else(|| anyhow!("invalid calldata hex"))?;
+        let mut tuple = Vec::new();
+        tuple.extend(abi_encode_address(to)?);
+        tuple.extend(abi_encode_bool(true));
+        tuple.extend(abi_encode_uint(U256::from(3 * 32))); // offset to bytes, within the tuple
+        tuple.extend(abi_encode_bytes(&call_data));
+        tuples.push(tuple);
+    }
+
+    let heads_len = 32 * tuples.len();
+    let mut offsets = Vec::with_capacity(tuples.len());
+    let mut elements = Vec::new();
+    let mut running_offset = heads_len;
+    for tuple in &tuples {
+        offsets.push(abi_encode_uint(U256::from(running_offset)));
+        running_offset += tuple.len();
+        elements.extend_from_slice(tuple);
+    }
+
+    let mut body = abi_encode_uint(U256::from(tuples.len())).to_vec();
+    for offset in offsets {
+        body.extend(offset);
+    }
+    body.extend(elements);
+
+    let mut encoded = hex_encode(&abi_encode_uint(U256::from(0x20u8))).to_string();
+    encoded.push_str(&hex_encode(&body));
+    Ok(format!("{SELECTOR_AGGREGATE3}{encoded}"))
+}
+
+/// Decodes the `(bool success, bytes returnData)[]` return value of
+/// `aggregate3()`.
+fn decode_aggregate3_result(bytes: &[u8]) -> Result<Vec<(bool, Vec<u8>)>> {
+    let array_offset = u256_to_usize(u256_from_be_slice(
+        bytes.get(0..32).ok_or_else(|| anyhow!("aggregate3 result truncated"))?,
+    ))?;
+    let length = u256_to_usize(u256_from_be_slice(
+        bytes
+            .get(array_offset..array_offset + 32)
+            .ok_or_else(|| anyhow!("aggregate3 result truncated (length)"))?,
+    ))?;
+
+    let elements_start = array_offset + 32;
+    let mut results = Vec::with_capacity(length);
+    for i in 0..length {
+        let offset_slot = bytes
+            .get(elements_start + i * 32..elements_start + (i + 1) * 32)
+            .ok_or_else(|| anyhow!("aggregate3 result truncated (element offset)"))?;
+        let tuple_start = elements_start + u256_to_usize(u256_from_be_slice(offset_slot))?;
+
+        let success = bytes
+            .get(tuple_start..tuple_start + 32)
+            .ok_or_else(|| anyhow!("aggregate3 result truncated (success)"))?
+            .iter()
+            .any(|&b| b != 0);
+        let bytes_offset = u256_to_usize(u256_from_be_slice(
+            bytes
+                .get(tuple_start + 32..tuple_start + 64)
+                .ok_or_else(|| anyhow!("aggregate3 result truncated (bytes offset)"))?,
+        ))?;
+        let bytes_start = tuple_start + bytes_offset;
+        let return_len = u256_to_usize(u256_from_be_slice(
+            bytes
+                .get(bytes_start..bytes_start + 32)
+                .ok_or_else(|| anyhow!("aggregate3 result truncated (returnData length)"))?,
+        ))?;
+        let return_data = bytes
+            .get(bytes_start + 32..bytes_start + 32 + return_len)
+            .ok_or_else(|| anyhow!("aggregate3 result truncated (returnData)"))?
+            .to_vec();
+
+        results.push((success, return_data));
+    }
+
+    Ok(results)
+}
+
+fn abi_encode_uint(value: U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    value.to_big_endian(&mut out);
+    out
+}
+
+fn abi_encode_bool(value: bool) -> [u8; 32] {
+    abi_encode_uint(U256::from(value as u8))
+}
+
+fn abi_encode_address(address: &str) -> Result<[u8; 32]> {
+    let bytes = hex_to_bytes(address).ok_or_else(|| anyhow!("invalid address hex"))?;
+    if bytes.len() != 20 {
+        return Err(anyhow!("address must be 20 bytes"));
+    }
+    let mut out = [0u8; 32];
+    out[12..].copy_from_slice(&bytes);
+    Ok(out)
+}
+
+fn abi_encode_bytes(data: &[u8]) -> Vec<u8> {
+    let mut out = abi_encode_uint(U256::from(data.len())).to_vec();
+    out.extend_from_slice(data);
+    let padding = (32 - (data.len() % 32)) % 32;
+    out.extend(std::iter::repeat(0u8).take(padding));
+    out
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn rpc_request(rpc_url: &str, body: serde_json::Value) -> Result<serde_json::Value> {
     let body_bytes = serde_json::to_vec(&body)?;
     let mut headers = std::collections::BTreeMap::new();
     headers.insert("Content-Type".to_string(), "application/json".to_string());
@@ -279,7 +879,7 @@ fn rpc_call(to: &str, data: &str) -> Result<String> {
         timeout_ms: Some(5_000),
     };
 
-    let response = http_fetch(RPC_URL.to_string(), Some(options));
+    let response = http_fetch(rpc_url.to_string(), Some(options));
     if !response.is_ok() {
         elog!(
             "HTTP Response was rejected: {} - {}",
@@ -290,12 +890,361 @@ fn rpc_call(to: &str, data: &str) -> Result<String> {
     }
 
     let json_value: serde_json::Value = serde_json::from_slice(&response.bytes)?;
+    if let Some(error) = json_value.get("error") {
+        return Err(anyhow!("RPC error: {error}"));
+    }
+    Ok(json_value)
+}
+
+/// Latest block's hash and `stateRoot`, used as the trust anchor for
+/// `eth_getProof` verification.
+fn rpc_get_block_header(rpc_url: &str) -> Result<(String, [u8; 32])> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getBlockByNumber",
+        "params": ["latest", false]
+    });
+    let json_value = rpc_request(rpc_url, body)?;
+    let block = json_value
+        .get("result")
+        .ok_or_else(|| anyhow!("RPC response missing block"))?;
+
+    let hash = block
+        .get("hash")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Block missing hash"))?
+        .to_string();
+    let state_root_hex = block
+        .get("stateRoot")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Block missing stateRoot"))?;
+    let state_root_bytes =
+        hex_to_bytes(state_root_hex).ok_or_else(|| anyhow!("Invalid stateRoot hex"))?;
+    if state_root_bytes.len() != 32 {
+        return Err(anyhow!("stateRoot is not 32 bytes"));
+    }
+    let mut state_root = [0u8; 32];
+    state_root.copy_from_slice(&state_root_bytes);
+
+    Ok((hash, state_root))
+}
+
+struct EthProof {
+    account_proof: Vec<Vec<u8>>,
+    storage_proofs: Vec<(U256, Vec<Vec<u8>>)>,
+}
+
+fn rpc_get_proof(
+    rpc_url: &str,
+    address: &str,
+    slots: &[U256],
+    block_hash: &str,
+) -> Result<EthProof> {
+    let keys: Vec<String> = slots.iter().map(|slot| format!("0x{slot:x}")).collect();
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getProof",
+        "params": [address, keys, block_hash]
+    });
+    let json_value = rpc_request(rpc_url, body)?;
     let result = json_value
         .get("result")
-        .and_then(|value| value.as_str())
-        .ok_or_else(|| anyhow!("RPC response missing result"))?;
+        .ok_or_else(|| anyhow!("RPC response missing proof result"))?;
 
-    Ok(result.to_string())
+    let account_proof = decode_hex_array(
+        result
+            .get("accountProof")
+            .ok_or_else(|| anyhow!("Proof missing accountProof"))?,
+    )?;
+
+    let storage_proof_json = result
+        .get("storageProof")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow!("Proof missing storageProof"))?;
+    if storage_proof_json.len() != slots.len() {
+        return Err(anyhow!("storageProof length mismatch"));
+    }
+
+    let mut storage_proofs = Vec::with_capacity(slots.len());
+    for (slot, entry) in slots.iter().zip(storage_proof_json) {
+        let proof = decode_hex_array(
+            entry
+                .get("proof")
+                .ok_or_else(|| anyhow!("storageProof entry missing proof"))?,
+        )?;
+        storage_proofs.push((*slot, proof));
+    }
+
+    Ok(EthProof {
+        account_proof,
+        storage_proofs,
+    })
+}
+
+fn decode_hex_array(value: &serde_json::Value) -> Result<Vec<Vec<u8>>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("expected a JSON array of hex strings"))?
+        .iter()
+        .map(|entry| {
+            entry
+                .as_str()
+                .and_then(hex_to_bytes)
+                .ok_or_else(|| anyhow!("invalid proof node hex"))
+        })
+        .collect()
+}
+
+/// Verifies the account proof for `address` against `state_root` and
+/// returns the account's RLP-decoded leaf value
+/// (`[nonce, balance, storageRoot, codeHash]`).
+fn verify_account_proof(
+    address: &str,
+    account_proof: &[Vec<u8>],
+    state_root: [u8; 32],
+) -> Result<Vec<RlpItem>> {
+    let address_bytes = hex_to_bytes(address).ok_or_else(|| anyhow!("invalid address hex"))?;
+    if address_bytes.len() != 20 {
+        return Err(anyhow!("address must be 20 bytes"));
+    }
+    let key = keccak256(&address_bytes);
+    let leaf_bytes = verify_trie_proof(&key, account_proof, state_root)?;
+    let account = rlp_as_list(&rlp_decode(&leaf_bytes)?)?.to_vec();
+    if account.len() != 4 {
+        return Err(anyhow!("malformed account RLP (expected 4 fields)"));
+    }
+    Ok(account)
+}
+
+fn account_storage_root(account: &[RlpItem]) -> Result<[u8; 32]> {
+    let storage_root_bytes = rlp_as_bytes(&account[2])?;
+    if storage_root_bytes.len() != 32 {
+        return Err(anyhow!("storageRoot is not 32 bytes"));
+    }
+    let mut storage_root = [0u8; 32];
+    storage_root.copy_from_slice(storage_root_bytes);
+    Ok(storage_root)
+}
+
+fn verify_storage_slot(slot: U256, proof: &EthProof, storage_root: [u8; 32]) -> Result<U256> {
+    let (_, storage_proof) = proof
+        .storage_proofs
+        .iter()
+        .find(|(s, _)| *s == slot)
+        .ok_or_else(|| anyhow!("missing storage proof for slot {slot}"))?;
+
+    let mut slot_bytes = [0u8; 32];
+    slot.to_big_endian(&mut slot_bytes);
+    let key = keccak256(&slot_bytes);
+
+    let value_bytes = verify_trie_proof(&key, storage_proof, storage_root)?;
+    let encoded_value = rlp_as_bytes(&rlp_decode(&value_bytes)?)?;
+    Ok(u256_from_be_slice(encoded_value))
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&hasher.finalize());
+    out
+}
+
+/// Minimal RLP item: either a byte string or a list of items.
+#[derive(Clone)]
+enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+fn rlp_as_bytes(item: &RlpItem) -> Result<&[u8]> {
+    match item {
+        RlpItem::Bytes(bytes) => Ok(bytes),
+        RlpItem::List(_) => Err(anyhow!("expected RLP bytes, got a list")),
+    }
+}
+
+fn rlp_as_list(item: &RlpItem) -> Result<&[RlpItem]> {
+    match item {
+        RlpItem::List(items) => Ok(items),
+        RlpItem::Bytes(_) => Err(anyhow!("expected an RLP list, got bytes")),
+    }
+}
+
+fn rlp_decode(input: &[u8]) -> Result<RlpItem> {
+    let (item, consumed) = rlp_decode_one(input)?;
+    if consumed != input.len() {
+        return Err(anyhow!("trailing bytes after RLP item"));
+    }
+    Ok(item)
+}
+
+fn rlp_decode_one(input: &[u8]) -> Result<(RlpItem, usize)> {
+    let prefix = *input.first().ok_or_else(|| anyhow!("truncated RLP input"))?;
+    match prefix {
+        0x00..=0x7f => Ok((RlpItem::Bytes(vec![prefix]), 1)),
+        0x80..=0xb7 => {
+            let len = (prefix - 0x80) as usize;
+            let data = input
+                .get(1..1 + len)
+                .ok_or_else(|| anyhow!("truncated RLP short string"))?;
+            Ok((RlpItem::Bytes(data.to_vec()), 1 + len))
+        }
+        0xb8..=0xbf => {
+            let len_of_len = (prefix - 0xb7) as usize;
+            let len = rlp_be_len(input, 1, len_of_len)?;
+            let start = 1 + len_of_len;
+            let data = input
+                .get(start..start + len)
+                .ok_or_else(|| anyhow!("truncated RLP long string"))?;
+            Ok((RlpItem::Bytes(data.to_vec()), start + len))
+        }
+        0xc0..=0xf7 => {
+            let len = (prefix - 0xc0) as usize;
+            let body = input
+                .get(1..1 + len)
+                .ok_or_else(|| anyhow!("truncated RLP short list"))?;
+            Ok((RlpItem::List(rlp_decode_list_body(body)?), 1 + len))
+        }
+        0xf8..=0xff => {
+            let len_of_len = (prefix - 0xf7) as usize;
+            let len = rlp_be_len(input, 1, len_of_len)?;
+            let start = 1 + len_of_len;
+            let body = input
+                .get(start..start + len)
+                .ok_or_else(|| anyhow!("truncated RLP long list"))?;
+            Ok((RlpItem::List(rlp_decode_list_body(body)?), start + len))
+        }
+    }
+}
+
+fn rlp_decode_list_body(mut body: &[u8]) -> Result<Vec<RlpItem>> {
+    let mut items = Vec::new();
+    while !body.is_empty() {
+        let (item, consumed) = rlp_decode_one(body)?;
+        items.push(item);
+        body = &body[consumed..];
+    }
+    Ok(items)
+}
+
+fn rlp_be_len(input: &[u8], start: usize, len_of_len: usize) -> Result<usize> {
+    let len_bytes = input
+        .get(start..start + len_of_len)
+        .ok_or_else(|| anyhow!("truncated RLP length prefix"))?;
+    if len_bytes.len() > std::mem::size_of::<usize>() {
+        return Err(anyhow!("RLP length prefix too large"));
+    }
+    Ok(len_bytes
+        .iter()
+        .fold(0usize, |acc, byte| (acc << 8) | *byte as usize))
+}
+
+fn bytes_to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push(byte >> 4);
+        out.push(byte & 0x0f);
+    }
+    out
+}
+
+/// Decodes a hex-prefix encoded path (used by extension/leaf nodes), returning
+/// the path nibbles and whether the node is a leaf.
+fn hex_prefix_decode(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let first = *encoded
+        .first()
+        .ok_or_else(|| anyhow!("empty hex-prefix encoded path"))?;
+    let flag = first >> 4;
+    let is_leaf = flag == 2 || flag == 3;
+    let is_odd = flag == 1 || flag == 3;
+
+    let mut path = Vec::new();
+    if is_odd {
+        path.push(first & 0x0f);
+    }
+    path.extend(bytes_to_nibbles(&encoded[1..]));
+    Ok((path, is_leaf))
+}
+
+/// A branch/extension child reference, per the MPT spec: a 32-byte keccak
+/// hash looked up in the proof list, or — when the child's own RLP encoding
+/// is under 32 bytes — the child node embedded directly in its parent.
+enum TrieChild {
+    Hashed([u8; 32]),
+    Embedded(Vec<RlpItem>),
+}
+
+fn decode_trie_child(item: RlpItem) -> Result<TrieChild> {
+    match item {
+        RlpItem::List(items) => Ok(TrieChild::Embedded(items)),
+        RlpItem::Bytes(bytes) => {
+            if bytes.is_empty() {
+                return Err(anyhow!("proof terminates at an empty branch slot"));
+            }
+            if bytes.len() != 32 {
+                return Err(anyhow!("branch child must reference a 32-byte hash"));
+            }
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&bytes);
+            Ok(TrieChild::Hashed(hash))
+        }
+    }
+}
+
+/// Walks a Merkle-Patricia trie proof for `key` starting at `root`, verifying
+/// that each hashed node's `keccak256` matches the hash referenced by its
+/// parent (an embedded child, inlined because its own RLP is under 32 bytes,
+/// needs no such check — it isn't hashed in the first place), and returns the
+/// RLP-encoded value at the leaf.
+fn verify_trie_proof(key: &[u8; 32], proof: &[Vec<u8>], root: [u8; 32]) -> Result<Vec<u8>> {
+    let mut path = bytes_to_nibbles(key);
+    let mut next = TrieChild::Hashed(root);
+    let mut proof_nodes = proof.iter();
+
+    loop {
+        let node = match next {
+            TrieChild::Hashed(expected_hash) => {
+                let node_bytes = proof_nodes
+                    .next()
+                    .ok_or_else(|| anyhow!("proof exhausted before reaching a leaf"))?;
+                if keccak256(node_bytes) != expected_hash {
+                    return Err(anyhow!("proof node hash does not match expected root"));
+                }
+                rlp_as_list(&rlp_decode(node_bytes)?)?.to_vec()
+            }
+            TrieChild::Embedded(items) => items,
+        };
+
+        match node.len() {
+            17 => {
+                if path.is_empty() {
+                    return Ok(rlp_as_bytes(&node[16])?.to_vec());
+                }
+                let nibble = path.remove(0) as usize;
+                let mut node = node;
+                next = decode_trie_child(node.swap_remove(nibble))?;
+            }
+            2 => {
+                let (segment, is_leaf) = hex_prefix_decode(rlp_as_bytes(&node[0])?)?;
+                if path.len() < segment.len() || path[..segment.len()] != segment[..] {
+                    return Err(anyhow!("proof path does not match requested key"));
+                }
+                path.drain(0..segment.len());
+                if is_leaf {
+                    if !path.is_empty() {
+                        return Err(anyhow!("leaf node reached with unconsumed path"));
+                    }
+                    return Ok(rlp_as_bytes(&node[1])?.to_vec());
+                }
+                let mut node = node;
+                next = decode_trie_child(node.swap_remove(1))?;
+            }
+            other => return Err(anyhow!("unexpected trie node arity {other}")),
+        }
+    }
 }
 
 fn parse_address_from_32byte(value: &str) -> Option<String> {
@@ -339,3 +1288,17 @@ fn u256_to_u128(value: U256) -> Result<u128> {
     }
     Ok(value.as_u128())
 }
+
+/// Reconciles the independently computed `price_scaled` values from each
+/// RPC endpoint by taking their median, so a single lying or stale
+/// provider can't move the reported price on its own.
+fn median_u128(data: &[u128]) -> u128 {
+    let mut sorted = data.to_vec();
+    sorted.sort_unstable();
+    let m = sorted.len();
+    if m % 2 == 0 {
+        sorted[m / 2 - 1].midpoint(sorted[m / 2])
+    } else {
+        sorted[m / 2]
+    }
+}
@@ -0,0 +1,198 @@
+use anyhow::{Result, anyhow};
+use ethabi::ethereum_types::U256;
+use seda_sdk_rs::{
+    Process,
+    elog,
+    http_fetch,
+    log,
+    http::{HttpFetchMethod, HttpFetchOptions},
+    bytes::ToBytes,
+};
+use serde_json::json;
+
+/// Independent Cronos RPC endpoints queried for every fetch. No single
+/// provider is trusted: at least [`RPC_QUORUM`] of them must return usable
+/// data, and the reported fees are each the median of the independently
+/// computed per-endpoint values.
+const RPC_URLS: &[&str] = &[
+    "https://mainnet-sticky.cronoslabs.com/v1/d3642384d334ff6ff1c4baebfdf3ef7d",
+    "https://cronos.blockpi.network/v1/rpc/0467a344ecda6f87cc7118bd02a14f5818a2f5ff",
+    "https://evm.cronos.org",
+];
+const RPC_QUORUM: usize = 2;
+
+/// EIP-1559 elasticity multiplier: the block gas target is half the gas limit.
+const ELASTICITY_MULTIPLIER: u128 = 2;
+/// EIP-1559 base fee max change denominator: the base fee can move by at
+/// most 1/8 per block.
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u128 = 8;
+
+pub fn execution_phase() -> Result<()> {
+    let mut reports = Vec::with_capacity(RPC_URLS.len());
+    for &rpc_url in RPC_URLS {
+        match compute_gas_report(rpc_url) {
+            Ok(report) => reports.push(report),
+            Err(err) => elog!("RPC endpoint {rpc_url} failed: {err}"),
+        }
+    }
+
+    if reports.len() < RPC_QUORUM {
+        return Err(anyhow!(
+            "Only {} of {} RPC endpoints returned data, need quorum of {RPC_QUORUM}",
+            reports.len(),
+            RPC_URLS.len()
+        ));
+    }
+
+    let base_fee = median_u128(&reports.iter().map(|r| r.base_fee).collect::<Vec<_>>());
+    let next_base_fee = median_u128(&reports.iter().map(|r| r.next_base_fee).collect::<Vec<_>>());
+
+    log!("Base fee (wei): {base_fee}, predicted next-block base fee (wei): {next_base_fee}");
+
+    // [base_fee, next_base_fee] as two concatenated u128 little-endian
+    // values in wei (unscaled), with no header or prefix bytes.
+    let mut reveal = Vec::with_capacity(32);
+    reveal.extend_from_slice(&base_fee.to_le_bytes());
+    reveal.extend_from_slice(&next_base_fee.to_le_bytes());
+    Process::success(&reveal);
+
+    Ok(())
+}
+
+struct GasReport {
+    base_fee: u128,
+    next_base_fee: u128,
+}
+
+/// Fetches the latest block from `rpc_url` and predicts the next-block
+/// base fee per the EIP-1559 update rule.
+fn compute_gas_report(rpc_url: &str) -> Result<GasReport> {
+    let block = rpc_get_latest_block(rpc_url)?;
+    let gas_target = block.gas_limit / ELASTICITY_MULTIPLIER;
+    let next_base_fee = predict_next_base_fee(block.base_fee_per_gas, block.gas_used, gas_target)?;
+
+    Ok(GasReport {
+        base_fee: block.base_fee_per_gas,
+        next_base_fee,
+    })
+}
+
+/// EIP-1559 base fee update rule: unchanged at the gas target, otherwise
+/// moves by up to `1 / BASE_FEE_MAX_CHANGE_DENOMINATOR` of the current base
+/// fee in proportion to how far `gas_used` is from `gas_target`.
+fn predict_next_base_fee(base_fee: u128, gas_used: u128, gas_target: u128) -> Result<u128> {
+    if gas_target == 0 {
+        return Ok(base_fee);
+    }
+
+    if gas_used == gas_target {
+        return Ok(base_fee);
+    }
+
+    if gas_used > gas_target {
+        let gas_delta = gas_used - gas_target;
+        let increase = std::cmp::max(1, scaled_delta(base_fee, gas_delta, gas_target)?);
+        Ok(base_fee.saturating_add(increase))
+    } else {
+        let gas_delta = gas_target - gas_used;
+        let decrease = scaled_delta(base_fee, gas_delta, gas_target)?;
+        Ok(base_fee.saturating_sub(decrease))
+    }
+}
+
+/// Computes `base_fee * gas_delta / gas_target / BASE_FEE_MAX_CHANGE_DENOMINATOR`
+/// via `U256` to avoid intermediate overflow.
+fn scaled_delta(base_fee: u128, gas_delta: u128, gas_target: u128) -> Result<u128> {
+    let value = U256::from(base_fee) * U256::from(gas_delta)
+        / U256::from(gas_target)
+        / U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+    u256_to_u128(value)
+}
+
+struct BlockHeader {
+    base_fee_per_gas: u128,
+    gas_used: u128,
+    gas_limit: u128,
+}
+
+fn rpc_get_latest_block(rpc_url: &str) -> Result<BlockHeader> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getBlockByNumber",
+        "params": ["latest", false]
+    });
+    let json_value = rpc_request(rpc_url, body)?;
+    let block = json_value
+        .get("result")
+        .ok_or_else(|| anyhow!("RPC response missing block"))?;
+
+    let base_fee_per_gas = parse_hex_field(block, "baseFeePerGas")?;
+    let gas_used = parse_hex_field(block, "gasUsed")?;
+    let gas_limit = parse_hex_field(block, "gasLimit")?;
+
+    Ok(BlockHeader {
+        base_fee_per_gas,
+        gas_used,
+        gas_limit,
+    })
+}
+
+fn parse_hex_field(block: &serde_json::Value, field: &str) -> Result<u128> {
+    let hex = block
+        .get(field)
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| anyhow!("Block missing {field}"))?;
+    u128::from_str_radix(hex.trim_start_matches("0x"), 16)
+        .map_err(|_| anyhow!("Invalid {field} hex"))
+}
+
+fn rpc_request(rpc_url: &str, body: serde_json::Value) -> Result<serde_json::Value> {
+    let body_bytes = serde_json::to_vec(&body)?;
+    let mut headers = std::collections::BTreeMap::new();
+    headers.insert("Content-Type".to_string(), "application/json".to_string());
+
+    let options = HttpFetchOptions {
+        method: HttpFetchMethod::Post,
+        headers,
+        body: Some(body_bytes.to_bytes()),
+        timeout_ms: Some(5_000),
+    };
+
+    let response = http_fetch(rpc_url.to_string(), Some(options));
+    if !response.is_ok() {
+        elog!(
+            "HTTP Response was rejected: {} - {}",
+            response.status,
+            String::from_utf8(response.bytes)?
+        );
+        return Err(anyhow!("RPC call failed"));
+    }
+
+    let json_value: serde_json::Value = serde_json::from_slice(&response.bytes)?;
+    if let Some(error) = json_value.get("error") {
+        return Err(anyhow!("RPC error: {error}"));
+    }
+    Ok(json_value)
+}
+
+fn u256_to_u128(value: U256) -> Result<u128> {
+    if value > U256::from(u128::MAX) {
+        return Err(anyhow!("Value exceeds u128 range"));
+    }
+    Ok(value.as_u128())
+}
+
+/// Reconciles a per-endpoint output field by taking its median, so a
+/// single lying or stale RPC provider can't move the reported value on
+/// its own.
+fn median_u128(data: &[u128]) -> u128 {
+    let mut sorted = data.to_vec();
+    sorted.sort_unstable();
+    let m = sorted.len();
+    if m % 2 == 0 {
+        sorted[m / 2 - 1].midpoint(sorted[m / 2])
+    } else {
+        sorted[m / 2]
+    }
+}
@@ -62,7 +62,60 @@ pub fn execution_phase() -> Result<()> {
 // 	"status": "OK"
 // }
 
-const ASSET_TYPES: [&str; 6] = ["cfd", "equity", "fx", "fx_r", "uslf_q", "uslf_t"];
+/// Oracle asset kind, identified by an explicit wire discriminant instead of
+/// the request's free-form `"cfd"`/`"equity"`/... asset-type string, so a
+/// downstream decoder can tell which kind of reveal it's looking at without
+/// guessing from which program produced it.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetKind {
+    Commodity = 0,
+    Equity = 1,
+    Fx = 2,
+    FxReciprocal = 3,
+    EquityQuote = 4,
+    EquityTrade = 5,
+}
+
+impl AssetKind {
+    fn from_asset_type(asset_type: &str) -> Option<Self> {
+        Some(match asset_type {
+            "cfd" => AssetKind::Commodity,
+            "equity" => AssetKind::Equity,
+            "fx" => AssetKind::Fx,
+            "fx_r" => AssetKind::FxReciprocal,
+            "uslf_q" => AssetKind::EquityQuote,
+            "uslf_t" => AssetKind::EquityTrade,
+            _ => return None,
+        })
+    }
+
+    fn is_allowed(self, symbol: &str) -> bool {
+        match self {
+            AssetKind::Commodity => ALLOWED_COMMODITIES.contains(&symbol),
+            AssetKind::Equity | AssetKind::EquityQuote | AssetKind::EquityTrade => {
+                ALLOWED_EQUITIES.contains(&symbol)
+            }
+            AssetKind::Fx | AssetKind::FxReciprocal => ALLOWED_FX.contains(&symbol),
+        }
+    }
+}
+
+impl TryFrom<u8> for AssetKind {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u8) -> Result<Self> {
+        Ok(match code {
+            0 => AssetKind::Commodity,
+            1 => AssetKind::Equity,
+            2 => AssetKind::Fx,
+            3 => AssetKind::FxReciprocal,
+            4 => AssetKind::EquityQuote,
+            5 => AssetKind::EquityTrade,
+            other => return Err(anyhow!("Unknown asset kind code: {other}")),
+        })
+    }
+}
 
 #[cfg(feature = "testnet")]
 const ALLOWED_COMMODITIES: [&str; 3] = ["WTI/USD", "BRN/USD", "XAU/USD"];
@@ -86,6 +139,33 @@ const ALLOWED_EQUITIES: [&str; 10] = [
 ];
 const ALLOWED_FX: [&str; 2] = ["EUR", "JPY"];
 
+const DEFAULT_DECIMALS: u8 = 6;
+
+/// Default staleness budget for a quote, used when the request doesn't
+/// override it via [`TLV_TAG_MAX_STALENESS_MS`]. Feeds freeze over
+/// weekends/holidays, so this is generous enough to tolerate normal
+/// inter-block latency without masking a genuinely frozen feed.
+const DEFAULT_MAX_STALENESS_MS: u64 = 5 * 60 * 1000;
+
+/// TLV record tags understood by [`parse_request`]. Any other tag is an
+/// unrecognized extension field and is skipped (but its bytes are still
+/// accounted for while walking the stream), so newer callers can add
+/// parameters without breaking this program.
+const TLV_TAG_SYMBOL: u8 = 0x01;
+const TLV_TAG_DECIMALS: u8 = 0x02;
+const TLV_TAG_MAX_STALENESS_MS: u8 = 0x03;
+const TLV_TAG_AGGREGATION_METHOD: u8 = 0x04;
+
+/// A decoded data request: one or more `"asset_type/symbol"` entries (same
+/// format the original single-symbol path used), in request order, plus the
+/// optional parameters that ride along with them.
+struct ParsedRequest {
+    symbols: Vec<String>,
+    decimals: u8,
+    max_staleness_ms: Option<u64>,
+    aggregation_method: Option<String>,
+}
+
 fn parse_input_pair(input: &str) -> Result<String> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
@@ -104,13 +184,158 @@ fn parse_input_pair(input: &str) -> Result<String> {
     Ok(trimmed.to_string())
 }
 
-fn is_allowed(asset_type: &str, symbol: &str) -> bool {
-    match asset_type {
-        "cfd" => ALLOWED_COMMODITIES.contains(&symbol),
-        "equity" | "uslf_q" | "uslf_t" => ALLOWED_EQUITIES.contains(&symbol),
-        "fx" | "fx_r" => ALLOWED_FX.contains(&symbol),
-        _ => false,
+/// Parses the raw data-request input. Two formats are understood:
+///
+/// - The extensible format: a TLV stream of `(type: u8, len: varint, value:
+///   bytes)` records (see the `TLV_TAG_*` constants). `TLV_TAG_SYMBOL` is
+///   repeatable and preserves order, so one request can batch multiple
+///   symbol fetches.
+/// - The original single-symbol format: a JSON `{ "pair": "..." }` object or
+///   a bare `"asset_type/symbol"` string, handled by [`parse_input_pair`].
+///
+/// TLV decoding is tried first; if the bytes aren't a well-formed TLV stream
+/// carrying at least one symbol record, the input falls back to the
+/// single-symbol path so older callers keep working unchanged.
+fn parse_request(input: &[u8]) -> Result<ParsedRequest> {
+    if let Ok(records) = decode_tlv(input) {
+        let symbols: Vec<String> = records
+            .iter()
+            .filter(|record| record.tag == TLV_TAG_SYMBOL)
+            .map(|record| String::from_utf8_lossy(&record.value).into_owned())
+            .collect();
+
+        if !symbols.is_empty() {
+            let decimals = records
+                .iter()
+                .find(|record| record.tag == TLV_TAG_DECIMALS)
+                .and_then(|record| record.value.first())
+                .copied()
+                .unwrap_or(DEFAULT_DECIMALS);
+
+            let max_staleness_ms = records
+                .iter()
+                .find(|record| record.tag == TLV_TAG_MAX_STALENESS_MS)
+                .map(|record| decode_varint(&record.value).map(|(value, _)| value))
+                .transpose()?;
+
+            let aggregation_method = records
+                .iter()
+                .find(|record| record.tag == TLV_TAG_AGGREGATION_METHOD)
+                .map(|record| String::from_utf8_lossy(&record.value).into_owned());
+
+            return Ok(ParsedRequest {
+                symbols,
+                decimals,
+                max_staleness_ms,
+                aggregation_method,
+            });
+        }
     }
+
+    let text = String::from_utf8(input.to_vec())?;
+    let symbol = parse_input_pair(&text)?;
+    Ok(ParsedRequest {
+        symbols: vec![symbol],
+        decimals: DEFAULT_DECIMALS,
+        max_staleness_ms: None,
+        aggregation_method: None,
+    })
+}
+
+struct TlvRecord {
+    tag: u8,
+    value: Vec<u8>,
+}
+
+fn decode_tlv(bytes: &[u8]) -> Result<Vec<TlvRecord>> {
+    let mut records = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let tag = bytes[pos];
+        pos += 1;
+
+        let (len, consumed) = decode_varint(&bytes[pos..])?;
+        pos += consumed;
+
+        let len = len as usize;
+        let end = pos
+            .checked_add(len)
+            .ok_or_else(|| anyhow!("TLV record length overflow"))?;
+        if end > bytes.len() {
+            return Err(anyhow!("TLV record truncated"));
+        }
+
+        records.push(TlvRecord {
+            tag,
+            value: bytes[pos..end].to_vec(),
+        });
+        pos = end;
+    }
+    Ok(records)
+}
+
+fn decode_varint(bytes: &[u8]) -> Result<(u64, usize)> {
+    let mut value: u64 = 0;
+    let mut shift: u32 = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        if shift >= 64 {
+            return Err(anyhow!("Varint too long"));
+        }
+        // The 10th continuation byte only has room for bit 0 of its 7
+        // payload bits before `value` overflows u64; reject anything wider
+        // instead of silently truncating it into a smaller, still-valid
+        // looking length.
+        if shift == 63 && (byte & 0x7f) > 1 {
+            return Err(anyhow!("Varint overflows u64"));
+        }
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+        shift += 7;
+    }
+    Err(anyhow!("Truncated varint"))
+}
+
+/// Wire format version for [`encode_reveal_record`]; bump when the record
+/// layout changes so a decoder can reject a reveal it no longer knows how
+/// to read instead of silently misinterpreting it.
+const REVEAL_WIRE_VERSION: u8 = 1;
+
+/// One compact, self-describing record per requested symbol:
+/// `[version: u8][asset_code: u8][price_scale: u8][bid: u128 LE]
+/// [ask: u128 LE][mid: u128 LE][event_time_ms: u64 LE]`, replacing the
+/// `int256[]` ABI array this program used to emit so a decoder can identify
+/// each symbol's asset kind and scale without assuming them out-of-band.
+/// Trade feeds carry no separate bid/ask, so bid = ask = mid = the last
+/// trade price. Shares its `[version][asset_code][price_scale]` header with
+/// `single-commodity-price`'s reveal and with the VVS tally's reveal in
+/// `seda-starter-kit`, which uses a disjoint `AssetKind` discriminant range
+/// (16+) so the `asset_code` byte stays unambiguous across all three.
+/// seda-core's pluggable-aggregation tally for GAS-CRO/WCRO-USDC keeps its
+/// separate `int256[]` protocol, so this is not yet a single repo-wide wire
+/// format. Consumed by this program's own `tally_phase`; `single-commodity-price`
+/// still has no local tally phase, so whatever consumes its reveal outside
+/// this repo needs to confirm it can parse the shared header.
+const REVEAL_RECORD_LEN: usize = 1 + 1 + 1 + 16 + 16 + 16 + 8;
+
+fn encode_reveal_record(
+    kind: AssetKind,
+    price_scale: u8,
+    bid: u128,
+    ask: u128,
+    mid: u128,
+    event_time_ms: u64,
+) -> [u8; REVEAL_RECORD_LEN] {
+    let mut record = [0u8; REVEAL_RECORD_LEN];
+    record[0] = REVEAL_WIRE_VERSION;
+    record[1] = kind as u8;
+    record[2] = price_scale;
+    record[3..19].copy_from_slice(&bid.to_le_bytes());
+    record[19..35].copy_from_slice(&ask.to_le_bytes());
+    record[35..51].copy_from_slice(&mid.to_le_bytes());
+    record[51..59].copy_from_slice(&event_time_ms.to_le_bytes());
+    record
 }
 
 #[derive(serde::Deserialize)]
@@ -127,31 +352,195 @@ struct TradeResponse {
 
 #[cfg(any(feature = "testnet", feature = "mainnet"))]
 pub fn execution_phase() -> Result<()> {
-    // Expected to be in the format "symbol,..." (e.g., "cfd/XAU/USD", "equity/AAPL")
-    use seda_sdk_rs::HttpFetchOptions;
-    let dr_inputs_raw = String::from_utf8(Process::get_inputs())?;
-    let dr_input = parse_input_pair(&dr_inputs_raw)?;
+    // Expected to be a TLV-encoded batch, or (for backward compatibility) a
+    // single "symbol,..." string (e.g., "cfd/XAU/USD", "equity/AAPL").
+    let dr_inputs_raw = Process::get_inputs();
+    let request = parse_request(&dr_inputs_raw)?;
+    log!(
+        "Requested {} symbol(s) at {} decimals (max_staleness_ms: {:?}, aggregation_method: {:?})",
+        request.symbols.len(),
+        request.decimals,
+        request.max_staleness_ms,
+        request.aggregation_method
+    );
+
+    let mut reveal = Vec::with_capacity(request.symbols.len() * REVEAL_RECORD_LEN);
+    for dr_input in &request.symbols {
+        let (asset_type, symbol) = dr_input.split_once('/').ok_or_else(|| {
+            elog!(
+                "Invalid input format. Expected format: 'cfd/BRN/USD' or 'equity/AAPL' or 'fx/EUR'"
+            );
+            Process::error("Invalid input format".as_bytes());
+            anyhow!("Invalid input format")
+        })?;
 
-    let (asset_type, symbol) = dr_input.split_once('/').ok_or_else(|| {
-        elog!("Invalid input format. Expected format: 'cfd/BRN/USD' or 'equity/AAPL' or 'fx/EUR'");
-        Process::error("Invalid input format".as_bytes());
-        anyhow::anyhow!("Invalid input format")
-    })?;
+        let Some(asset_kind) = AssetKind::from_asset_type(asset_type) else {
+            elog!("Invalid asset type: {asset_type}");
+            Process::error("Invalid asset type".as_bytes());
+            return Ok(());
+        };
+        let symbol = symbol.to_uppercase();
+        if !asset_kind.is_allowed(&symbol) {
+            elog!("Unsupported symbol for asset type: {asset_type} {symbol}");
+            Process::error("Unsupported symbol".as_bytes());
+            return Ok(());
+        }
+        log!("Fetching price for asset type: {asset_type}, symbol: {symbol}");
+
+        let quote = fetch_quote(asset_type, &symbol)?;
+        log!(
+            "Fetched quote: bid={}, ask={}, mid={}, event_time_ms={}",
+            quote.bid,
+            quote.ask,
+            quote.mid,
+            quote.event_time_ms
+        );
 
-    if !ASSET_TYPES.contains(&asset_type) {
-        elog!("Invalid asset type. Expected one of: {:?}", ASSET_TYPES);
-        Process::error("Invalid asset type".as_bytes());
-        return Ok(());
+        // This is a per-node staleness check only: it catches a frozen feed
+        // on the node that fetched it. A node reporting a quote whose
+        // timestamp is merely inconsistent with the rest of the cohort
+        // survives this check; that's instead rejected cross-reveal, per
+        // symbol, in `tally_phase`'s `filter_timestamp_outliers`.
+        let max_staleness_ms = request.max_staleness_ms.unwrap_or(DEFAULT_MAX_STALENESS_MS);
+        let age_ms = now_ms()?.saturating_sub(quote.event_time_ms);
+        if age_ms > max_staleness_ms {
+            elog!(
+                "Stale quote for {asset_type}/{symbol}: age_ms={age_ms} exceeds max_staleness_ms={max_staleness_ms}"
+            );
+            Process::error("Stale quote".as_bytes());
+            return Ok(());
+        }
+
+        reveal.extend_from_slice(&encode_reveal_record(
+            asset_kind,
+            request.decimals,
+            rescale_decimals(quote.bid, FEED_SCALE_DECIMALS, request.decimals as u32),
+            rescale_decimals(quote.ask, FEED_SCALE_DECIMALS, request.decimals as u32),
+            rescale_decimals(quote.mid, FEED_SCALE_DECIMALS, request.decimals as u32),
+            quote.event_time_ms,
+        ));
     }
-    let symbol = symbol.to_uppercase();
-    if !is_allowed(asset_type, &symbol) {
-        elog!("Unsupported symbol for asset type: {asset_type} {symbol}");
-        Process::error("Unsupported symbol".as_bytes());
-        return Ok(());
+
+    Process::success(&reveal);
+
+    Ok(())
+}
+
+/// Milliseconds since the Unix epoch, per the executing node's local clock.
+/// Staleness gating is a per-node pre-check, not part of the consensus
+/// value itself, so nodes disagreeing by a few seconds around the
+/// `max_staleness_ms` boundary is acceptable.
+#[cfg(any(feature = "testnet", feature = "mainnet"))]
+fn now_ms() -> Result<u64> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64)
+}
+
+/// A single symbol's quote, unified across Quote feeds (which carry a real
+/// bid/ask spread) and Trade feeds (which only carry a last-traded price).
+/// Prices are fixed-point `u128` at [`FEED_SCALE_DECIMALS`], rescaled to the
+/// request's requested decimals only once, at the point they're encoded.
+struct Quote {
+    bid: u128,
+    ask: u128,
+    mid: u128,
+    event_time_ms: u64,
+}
+
+/// Precision prices are parsed at before being rescaled to the caller's
+/// requested decimals. Comfortably above any dxFeed price's native decimal
+/// precision, so rescaling down never loses a significant digit the feed
+/// itself provided.
+const FEED_SCALE_DECIMALS: u32 = 18;
+
+/// Rescales a fixed-point `u128` from `from_decimals` to `to_decimals` places
+/// using pure integer arithmetic, so converting a feed price to the
+/// caller's requested decimals never goes through a float.
+fn rescale_decimals(value: u128, from_decimals: u32, to_decimals: u32) -> u128 {
+    if to_decimals >= from_decimals {
+        value.saturating_mul(pow10_u128(to_decimals - from_decimals))
+    } else {
+        value / pow10_u128(from_decimals - to_decimals)
+    }
+}
+
+fn pow10_u128(exp: u32) -> u128 {
+    let mut result: u128 = 1;
+    for _ in 0..exp {
+        result = result.saturating_mul(10);
+    }
+    result
+}
+
+/// Rescales a dxFeed quote field to a fixed-point `u128` at `scale` decimal
+/// places, accepting a JSON number, a decimal string, or a `0x`-prefixed hex
+/// string (treated as an already-scaled raw integer). JSON numbers and
+/// decimal strings are parsed as text rather than through `f64`, so large
+/// commodity/equity prices don't pick up float rounding on the way in.
+fn scaled_u128_from_value(value: &serde_json::Value, scale: u32) -> Result<u128> {
+    match value {
+        serde_json::Value::Number(number) => decimal_str_to_u128(&number.to_string(), scale)
+            .ok_or_else(|| anyhow!("Invalid numeric value: {number}")),
+        serde_json::Value::String(text) => {
+            if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                u128::from_str_radix(hex, 16).map_err(|err| anyhow!("Invalid hex value {text}: {err}"))
+            } else {
+                decimal_str_to_u128(text, scale).ok_or_else(|| anyhow!("Invalid decimal value: {text}"))
+            }
+        }
+        other => Err(anyhow!("Unsupported numeric representation: {other}")),
+    }
+}
+
+fn decimal_str_to_u128(value: &str, scale: u32) -> Option<u128> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let mut parts = trimmed.split('.');
+    let whole = parts.next().unwrap_or("0");
+    let fraction = parts.next().unwrap_or("");
+
+    if parts.next().is_some() {
+        return None;
     }
-    log!("Fetching price for asset type: {asset_type}, symbol: {symbol}");
 
-    let url = [API_URL, asset_type, "/", &symbol].concat();
+    if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut combined = String::new();
+    combined.push_str(whole);
+
+    if scale > 0 {
+        let mut frac = fraction.to_string();
+        let target_len = scale as usize;
+        if frac.len() < target_len {
+            frac.push_str(&"0".repeat(target_len - frac.len()));
+        } else if frac.len() > target_len {
+            frac.truncate(target_len);
+        }
+        combined.push_str(&frac);
+    }
+
+    let trimmed_combined = combined.trim_start_matches('0');
+    if trimmed_combined.is_empty() {
+        return Some(0);
+    }
+
+    trimmed_combined.parse::<u128>().ok()
+}
+
+/// Fetches and extracts the bid/ask/mid/event-time quote (Quote feeds) or
+/// last trade price (Trade feeds) for a single already-validated
+/// `asset_type`/`symbol` pair.
+#[cfg(any(feature = "testnet", feature = "mainnet"))]
+fn fetch_quote(asset_type: &str, symbol: &str) -> Result<Quote> {
+    use seda_sdk_rs::HttpFetchOptions;
+
+    let url = [API_URL, asset_type, "/", symbol].concat();
     let response = proxy_http_fetch(
         url,
         Some(PROXY_PUBLIC_KEY.to_string()),
@@ -170,8 +559,7 @@ pub fn execution_phase() -> Result<()> {
             response.status,
             String::from_utf8(response.bytes)?
         );
-        Process::error("Error while fetching commodity price".as_bytes());
-        return Ok(());
+        return Err(anyhow!("Error while fetching commodity price"));
     }
 
     let path = match asset_type {
@@ -184,28 +572,70 @@ pub fn execution_phase() -> Result<()> {
     };
 
     // Parse the API response as defined earlier.
-    let price = match asset_type {
+    match asset_type {
         "cfd" | "fx" | "fx_r" | "uslf_q" => {
-            serde_json::from_slice::<QuoteResponse>(&response.bytes)?
+            let quote = serde_json::from_slice::<QuoteResponse>(&response.bytes)?
                 .quote
                 .get(&path)
-                .and_then(|quote| quote.get("askPrice"))
-                .and_then(|price| price.as_f64())
-        }
-        "equity" | "uslf_t" => serde_json::from_slice::<TradeResponse>(&response.bytes)?
-            .trade
-            .get(&path)
-            .and_then(|quote| quote.get("price"))
-            .and_then(|price| price.as_f64()),
-        _ => unreachable!(),
-    }
-    .ok_or_else(|| anyhow::anyhow!("Price not found in response"))?;
+                .cloned()
+                .ok_or_else(|| anyhow!("Price not found in response"))?;
 
-    let price_lossless = (price * 1_000_000.0) as u128;
-    log!("Fetched price: {price_lossless:?}");
+            let bid = quote
+                .get("bidPrice")
+                .ok_or_else(|| anyhow!("bidPrice not found in response"))
+                .and_then(|price| scaled_u128_from_value(price, FEED_SCALE_DECIMALS))?;
+            let ask = quote
+                .get("askPrice")
+                .ok_or_else(|| anyhow!("askPrice not found in response"))
+                .and_then(|price| scaled_u128_from_value(price, FEED_SCALE_DECIMALS))?;
 
-    // Scaled 1e6 u128, serialized little-endian for tally.
-    Process::success(&price_lossless.to_le_bytes());
+            // `eventTime` is 0 on this feed in practice; the freshest of the
+            // two quote-side timestamps is the best available signal of
+            // when the quote was struck.
+            let event_time_ms = [
+                quote.get("eventTime").and_then(|t| t.as_u64()),
+                quote.get("bidTime").and_then(|t| t.as_u64()),
+                quote.get("askTime").and_then(|t| t.as_u64()),
+            ]
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(0);
 
-    Ok(())
+            Ok(Quote {
+                bid,
+                ask,
+                mid: (bid + ask) / 2,
+                event_time_ms,
+            })
+        }
+        "equity" | "uslf_t" => {
+            let trade = serde_json::from_slice::<TradeResponse>(&response.bytes)?
+                .trade
+                .get(&path)
+                .cloned()
+                .ok_or_else(|| anyhow!("Price not found in response"))?;
+
+            let price = trade
+                .get("price")
+                .ok_or_else(|| anyhow!("price not found in response"))
+                .and_then(|price| scaled_u128_from_value(price, FEED_SCALE_DECIMALS))?;
+            let event_time_ms = [
+                trade.get("eventTime").and_then(|t| t.as_u64()),
+                trade.get("time").and_then(|t| t.as_u64()),
+            ]
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(0);
+
+            Ok(Quote {
+                bid: price,
+                ask: price,
+                mid: price,
+                event_time_ms,
+            })
+        }
+        _ => unreachable!(),
+    }
 }
@@ -0,0 +1,242 @@
+use anyhow::{Result, anyhow};
+use seda_sdk_rs::{Process, elog, get_reveals, log};
+
+/// Wire format version emitted by `execution_phase`'s `encode_reveal_record`;
+/// a reveal carrying any other version is rejected rather than
+/// misinterpreted.
+const REVEAL_WIRE_VERSION: u8 = 1;
+
+/// Byte length of a single symbol's record; see `execution_phase`'s
+/// `encode_reveal_record` for the field layout.
+const REVEAL_RECORD_LEN: usize = 1 + 1 + 1 + 16 + 16 + 16 + 8;
+
+/// MAD multiplier for rejecting a symbol's record from a reveal whose
+/// `event_time_ms` is inconsistent with the rest of the cohort's — on top of
+/// (not instead of) each node's own per-reveal staleness gate in
+/// `execution_phase`, which only catches a frozen feed on the node that
+/// fetched it, not a node reporting a quote struck at a different moment
+/// than everyone else's.
+const TIMESTAMP_OUTLIER_K: u64 = 5;
+
+/// Minimum number of records that must survive timestamp-consistency
+/// filtering for a symbol; below this the tally has no meaningful consensus
+/// left to report for it.
+const MIN_SURVIVORS_AFTER_FILTER: usize = 1;
+
+pub fn tally_phase() -> Result<()> {
+    if let Err(err) = tally_phase_inner() {
+        elog!("Tally error: {err}");
+        Process::error(format!("Tally error: {err}").as_bytes());
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+struct Record {
+    asset_code: u8,
+    price_scale: u8,
+    bid: u128,
+    ask: u128,
+    mid: u128,
+    event_time_ms: u64,
+}
+
+fn tally_phase_inner() -> Result<()> {
+    let reveals = get_reveals()?;
+    let mut decoded: Vec<Vec<Record>> = Vec::with_capacity(reveals.len());
+
+    for reveal in reveals {
+        match decode_reveal(&reveal.body.reveal) {
+            Ok(records) => decoded.push(records),
+            Err(err) => elog!("Reveal decode failed: {err}"),
+        }
+    }
+
+    if decoded.is_empty() {
+        Process::error("No consensus among revealed results".as_bytes());
+        return Ok(());
+    }
+
+    // Reveals disagreeing on the requested symbol count can't be aligned
+    // record-by-record, so only the majority's count is used; the rest are
+    // treated as misbehaving rather than as a tie-break signal.
+    let symbol_count = majority_record_count(&decoded);
+    let aligned: Vec<&Vec<Record>> = decoded.iter().filter(|r| r.len() == symbol_count).collect();
+    if aligned.is_empty() || symbol_count == 0 {
+        return Err(anyhow!("No reveals agreed on a symbol count"));
+    }
+
+    let mut output = Vec::with_capacity(symbol_count * REVEAL_RECORD_LEN);
+    for idx in 0..symbol_count {
+        let column: Vec<Record> = aligned.iter().map(|records| records[idx]).collect();
+        let survivors = filter_timestamp_outliers(&column, TIMESTAMP_OUTLIER_K);
+        if survivors.len() < MIN_SURVIVORS_AFTER_FILTER {
+            return Err(anyhow!(
+                "Only {} of {} reveals survived timestamp-consistency filtering for symbol {idx}",
+                survivors.len(),
+                column.len()
+            ));
+        }
+
+        let (asset_code, price_scale) = majority_header(&column);
+        let bid = median_u128(&survivors.iter().map(|r| r.bid).collect::<Vec<_>>());
+        let ask = median_u128(&survivors.iter().map(|r| r.ask).collect::<Vec<_>>());
+        let mid = median_u128(&survivors.iter().map(|r| r.mid).collect::<Vec<_>>());
+        let event_time_ms =
+            median_u64(&survivors.iter().map(|r| r.event_time_ms).collect::<Vec<_>>());
+        log!(
+            "Symbol {idx}: bid={bid}, ask={ask}, mid={mid}, event_time_ms={event_time_ms} ({}/{} reveals agreed)",
+            survivors.len(),
+            column.len()
+        );
+
+        output.extend_from_slice(&encode_record(
+            asset_code,
+            price_scale,
+            bid,
+            ask,
+            mid,
+            event_time_ms,
+        ));
+    }
+
+    Process::success(&output);
+
+    Ok(())
+}
+
+/// Decodes a reveal into one [`Record`] per requested symbol, per the
+/// `[version][asset_code][price_scale][bid: u128 LE][ask: u128 LE]
+/// [mid: u128 LE][event_time_ms: u64 LE]` layout `execution_phase`'s
+/// `encode_reveal_record` produces.
+fn decode_reveal(bytes: &[u8]) -> Result<Vec<Record>> {
+    if bytes.is_empty() || bytes.len() % REVEAL_RECORD_LEN != 0 {
+        return Err(anyhow!(
+            "Reveal length {} is not a multiple of the {REVEAL_RECORD_LEN}-byte record size",
+            bytes.len()
+        ));
+    }
+
+    bytes
+        .chunks_exact(REVEAL_RECORD_LEN)
+        .map(|chunk| {
+            if chunk[0] != REVEAL_WIRE_VERSION {
+                return Err(anyhow!("Unsupported reveal wire version: {}", chunk[0]));
+            }
+            Ok(Record {
+                asset_code: chunk[1],
+                price_scale: chunk[2],
+                bid: u128::from_le_bytes(chunk[3..19].try_into().unwrap()),
+                ask: u128::from_le_bytes(chunk[19..35].try_into().unwrap()),
+                mid: u128::from_le_bytes(chunk[35..51].try_into().unwrap()),
+                event_time_ms: u64::from_le_bytes(chunk[51..59].try_into().unwrap()),
+            })
+        })
+        .collect()
+}
+
+fn encode_record(
+    asset_code: u8,
+    price_scale: u8,
+    bid: u128,
+    ask: u128,
+    mid: u128,
+    event_time_ms: u64,
+) -> [u8; REVEAL_RECORD_LEN] {
+    let mut record = [0u8; REVEAL_RECORD_LEN];
+    record[0] = REVEAL_WIRE_VERSION;
+    record[1] = asset_code;
+    record[2] = price_scale;
+    record[3..19].copy_from_slice(&bid.to_le_bytes());
+    record[19..35].copy_from_slice(&ask.to_le_bytes());
+    record[35..51].copy_from_slice(&mid.to_le_bytes());
+    record[51..59].copy_from_slice(&event_time_ms.to_le_bytes());
+    record
+}
+
+/// Reveals should all agree on how many symbols were requested (they derive
+/// it deterministically from the same data request input), so a minority
+/// reporting a different count is treated as misbehaving rather than as a
+/// tie-break signal.
+fn majority_record_count(decoded: &[Vec<Record>]) -> usize {
+    decoded
+        .iter()
+        .map(Vec::len)
+        .max_by_key(|candidate| decoded.iter().filter(|r| r.len() == *candidate).count())
+        .unwrap_or(0)
+}
+
+/// Reveals should all agree on a symbol's `asset_code`/`price_scale` (they
+/// derive both deterministically from the same data request input), so a
+/// minority reporting different values is treated as misbehaving rather
+/// than as a tie-break signal.
+fn majority_header(column: &[Record]) -> (u8, u8) {
+    column
+        .iter()
+        .map(|r| (r.asset_code, r.price_scale))
+        .max_by_key(|candidate| {
+            column
+                .iter()
+                .filter(|r| (r.asset_code, r.price_scale) == *candidate)
+                .count()
+        })
+        .unwrap_or((0, 0))
+}
+
+/// Rejects a symbol's record from a reveal whose `event_time_ms` is more
+/// than `k` median-absolute-deviations from the cohort's median timestamp
+/// for that symbol, so a node reporting a quote struck far earlier or later
+/// than everyone else's can't drag the aggregate with a price quoted at a
+/// different moment. `k == 0` disables the filter; a `MAD` of zero (the
+/// honest majority agrees exactly) also disables it, since there's no
+/// meaningful spread to measure outliers against.
+fn filter_timestamp_outliers(column: &[Record], k: u64) -> Vec<Record> {
+    if k == 0 || column.len() < 3 {
+        return column.to_vec();
+    }
+
+    let times: Vec<u64> = column.iter().map(|r| r.event_time_ms).collect();
+    let med = median_u64(&times);
+    let mut deviations: Vec<u64> = times.iter().map(|t| t.abs_diff(med)).collect();
+    deviations.sort_unstable();
+    let mad = median_u64(&deviations);
+    if mad == 0 {
+        return column.to_vec();
+    }
+
+    let threshold = mad.saturating_mul(k);
+    let survivors: Vec<Record> = column
+        .iter()
+        .copied()
+        .filter(|r| r.event_time_ms.abs_diff(med) <= threshold)
+        .collect();
+
+    let rejected = column.len() - survivors.len();
+    if rejected > 0 {
+        log!("Rejected {rejected} record(s) with inconsistent event_time_ms (k={k}, MAD={mad})");
+    }
+    survivors
+}
+
+fn median_u128(values: &[u128]) -> u128 {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        sorted[mid - 1].midpoint(sorted[mid])
+    } else {
+        sorted[mid]
+    }
+}
+
+fn median_u64(values: &[u64]) -> u64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        sorted[mid - 1].midpoint(sorted[mid])
+    } else {
+        sorted[mid]
+    }
+}
@@ -19,6 +19,119 @@ const ALLOWED_COMMODITIES: [&str; 11] = [
     "DJI", "XPT", "WTI", "BRN", "SPX", "CAU", "XPD", "CUC", "NDX", "NGC", "XAG",
 ];
 
+const PRICE_SCALE_DECIMALS: u8 = 6;
+
+/// Wire format version for [`encode_reveal`]; bump when the record layout
+/// changes so a decoder can reject a reveal it no longer knows how to read
+/// instead of silently misinterpreting it.
+const REVEAL_WIRE_VERSION: u8 = 1;
+
+/// Oracle asset kind carried in the reveal's `asset_code` byte, so a decoder
+/// can tell which kind of price this is without guessing from which program
+/// produced it. This program only ever reports commodities.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetKind {
+    Commodity = 0,
+}
+
+impl TryFrom<u8> for AssetKind {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u8) -> Result<Self> {
+        match code {
+            0 => Ok(AssetKind::Commodity),
+            other => Err(anyhow!("Unknown asset kind code: {other}")),
+        }
+    }
+}
+
+/// Compact, self-describing reveal: `[version: u8][asset_code: u8]
+/// [price_scale: u8][price: u128 LE]`, replacing the bare little-endian
+/// integer this program used to emit so a decoder can identify the asset
+/// kind and scale without assuming them out-of-band. Shared with
+/// `generic-dxfeed`'s per-symbol record (same header, before its own
+/// bid/ask/mid/event-time fields) and with the VVS tally's reveal in
+/// `seda-starter-kit`, which uses the identical header but a disjoint
+/// `AssetKind` discriminant range (16+ vs. the 0-5 codes used here and in
+/// `generic-dxfeed`) so the
+/// `asset_code` byte stays unambiguous across all three. seda-core's
+/// pluggable-aggregation tally for GAS-CRO/WCRO-USDC keeps its separate
+/// `int256[]` protocol, so this is not yet a single repo-wide wire format.
+/// This program has no local tally phase, so whatever consumes
+/// this reveal outside this repo needs to confirm it can parse this layout
+/// before relying on it.
+const REVEAL_LEN: usize = 1 + 1 + 1 + 16;
+
+fn encode_reveal(kind: AssetKind, price_scale: u8, price: u128) -> [u8; REVEAL_LEN] {
+    let mut reveal = [0u8; REVEAL_LEN];
+    reveal[0] = REVEAL_WIRE_VERSION;
+    reveal[1] = kind as u8;
+    reveal[2] = price_scale;
+    reveal[3..19].copy_from_slice(&price.to_le_bytes());
+    reveal
+}
+
+/// Rescales a dxFeed quote field to a fixed-point `u128` at `scale` decimal
+/// places, accepting a JSON number, a decimal string, or a `0x`-prefixed hex
+/// string (treated as an already-scaled raw integer). JSON numbers and
+/// decimal strings are parsed as text rather than through `f64`, so large
+/// commodity prices don't pick up float rounding on the way in.
+fn scaled_u128_from_value(value: &serde_json::Value, scale: u32) -> Result<u128> {
+    match value {
+        serde_json::Value::Number(number) => decimal_str_to_u128(&number.to_string(), scale)
+            .ok_or_else(|| anyhow!("Invalid numeric value: {number}")),
+        serde_json::Value::String(text) => {
+            if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                u128::from_str_radix(hex, 16).map_err(|err| anyhow!("Invalid hex value {text}: {err}"))
+            } else {
+                decimal_str_to_u128(text, scale).ok_or_else(|| anyhow!("Invalid decimal value: {text}"))
+            }
+        }
+        other => Err(anyhow!("Unsupported numeric representation: {other}")),
+    }
+}
+
+fn decimal_str_to_u128(value: &str, scale: u32) -> Option<u128> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let mut parts = trimmed.split('.');
+    let whole = parts.next().unwrap_or("0");
+    let fraction = parts.next().unwrap_or("");
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut combined = String::new();
+    combined.push_str(whole);
+
+    if scale > 0 {
+        let mut frac = fraction.to_string();
+        let target_len = scale as usize;
+        if frac.len() < target_len {
+            frac.push_str(&"0".repeat(target_len - frac.len()));
+        } else if frac.len() > target_len {
+            frac.truncate(target_len);
+        }
+        combined.push_str(&frac);
+    }
+
+    let trimmed_combined = combined.trim_start_matches('0');
+    if trimmed_combined.is_empty() {
+        return Some(0);
+    }
+
+    trimmed_combined.parse::<u128>().ok()
+}
+
 fn parse_input_pair(input: &str) -> Result<String> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
@@ -122,17 +235,16 @@ pub fn execution_phase() -> Result<()> {
         }
     };
 
-    let price = response_data
+    let price_lossless = response_data
         .quote
         .get(&format!("{symbol}/USD:BFX"))
         .and_then(|quote| quote.get("askPrice"))
-        .and_then(|price| price.as_f64())
-        .ok_or_else(|| anyhow::anyhow!("Price not found in response"))?;
-    let price_lossless = (price * 1_000_000.0) as u128;
+        .ok_or_else(|| anyhow::anyhow!("Price not found in response"))
+        .and_then(|price| scaled_u128_from_value(price, PRICE_SCALE_DECIMALS as u32))?;
     log!("Fetched price: {price_lossless:?}");
 
-    // Scaled 1e6 u128, serialized little-endian for tally.
-    Process::success(&price_lossless.to_le_bytes());
+    let reveal = encode_reveal(AssetKind::Commodity, PRICE_SCALE_DECIMALS, price_lossless);
+    Process::success(&reveal);
 
     Ok(())
 }
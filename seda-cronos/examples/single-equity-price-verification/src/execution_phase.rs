@@ -1,5 +1,7 @@
 use anyhow::{Result, anyhow};
 #[cfg(any(feature = "testnet", feature = "mainnet"))]
+use ethabi::{Token, ethereum_types::U256};
+#[cfg(any(feature = "testnet", feature = "mainnet"))]
 use seda_sdk_rs::{Process, elog, log, proxy_http_fetch};
 
 #[cfg(feature = "testnet")]
@@ -16,22 +18,105 @@ const ALLOWED_EQUITIES: [&str; 10] = [
     "SPY", "TSLA", "MSFT", "AAPL", "AMZN", "NVDA", "GOOG", "META", "UNH", "VAPE",
 ];
 
-fn parse_input_pair(input: &str) -> Result<String> {
+/// Default staleness budget for an equity quote, used when the request
+/// doesn't override it. Equity quotes freeze over weekends/holidays, so this
+/// is generous enough to tolerate normal inter-block latency without masking
+/// a genuinely frozen feed.
+const DEFAULT_MAX_STALENESS_MS: u64 = 5 * 60 * 1000;
+
+const PRICE_SCALE_DECIMALS: u32 = 6;
+
+/// Rescales a dxFeed quote field to a fixed-point `u128` at `scale` decimal
+/// places, accepting a JSON number, a decimal string, or a `0x`-prefixed hex
+/// string (treated as an already-scaled raw integer). JSON numbers and
+/// decimal strings are parsed as text rather than through `f64`, so large
+/// equity prices don't pick up float rounding on the way in.
+fn scaled_u128_from_value(value: &serde_json::Value, scale: u32) -> Result<u128> {
+    match value {
+        serde_json::Value::Number(number) => decimal_str_to_u128(&number.to_string(), scale)
+            .ok_or_else(|| anyhow!("Invalid numeric value: {number}")),
+        serde_json::Value::String(text) => {
+            if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                u128::from_str_radix(hex, 16).map_err(|err| anyhow!("Invalid hex value {text}: {err}"))
+            } else {
+                decimal_str_to_u128(text, scale).ok_or_else(|| anyhow!("Invalid decimal value: {text}"))
+            }
+        }
+        other => Err(anyhow!("Unsupported numeric representation: {other}")),
+    }
+}
+
+fn decimal_str_to_u128(value: &str, scale: u32) -> Option<u128> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let mut parts = trimmed.split('.');
+    let whole = parts.next().unwrap_or("0");
+    let fraction = parts.next().unwrap_or("");
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut combined = String::new();
+    combined.push_str(whole);
+
+    if scale > 0 {
+        let mut frac = fraction.to_string();
+        let target_len = scale as usize;
+        if frac.len() < target_len {
+            frac.push_str(&"0".repeat(target_len - frac.len()));
+        } else if frac.len() > target_len {
+            frac.truncate(target_len);
+        }
+        combined.push_str(&frac);
+    }
+
+    let trimmed_combined = combined.trim_start_matches('0');
+    if trimmed_combined.is_empty() {
+        return Some(0);
+    }
+
+    trimmed_combined.parse::<u128>().ok()
+}
+
+fn parse_input_pair(input: &str) -> Result<(String, u64)> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
         return Err(anyhow!("No input provided"));
     }
 
     if let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed) {
+        let max_staleness_ms = value
+            .get("max_staleness_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_MAX_STALENESS_MS);
         if let Some(pair) = value.get("pair").and_then(|v| v.as_str()) {
-            return Ok(pair.to_string());
+            return Ok((pair.to_string(), max_staleness_ms));
         }
         if let Some(pair) = value.as_str() {
-            return Ok(pair.to_string());
+            return Ok((pair.to_string(), max_staleness_ms));
         }
     }
 
-    Ok(trimmed.to_string())
+    Ok((trimmed.to_string(), DEFAULT_MAX_STALENESS_MS))
+}
+
+/// Milliseconds since the Unix epoch, per the executing node's local clock.
+/// Staleness gating is a per-node pre-check, not part of the consensus
+/// value itself, so nodes disagreeing by a few seconds around the
+/// `max_staleness_ms` boundary is acceptable.
+#[cfg(any(feature = "testnet", feature = "mainnet"))]
+fn now_ms() -> Result<u64> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64)
 }
 
 #[cfg(not(any(feature = "testnet", feature = "mainnet")))]
@@ -49,7 +134,8 @@ pub fn execution_phase() -> Result<()> {
     }
 
     let dr_inputs_raw = String::from_utf8(Process::get_inputs())?;
-    let symbol = parse_input_pair(&dr_inputs_raw)?.to_uppercase();
+    let (symbol, max_staleness_ms) = parse_input_pair(&dr_inputs_raw)?;
+    let symbol = symbol.to_uppercase();
     if !ALLOWED_EQUITIES.contains(&symbol.as_str()) {
         elog!("Unsupported equity symbol: {symbol}");
         Process::error("Unsupported equity".as_bytes());
@@ -74,17 +160,68 @@ pub fn execution_phase() -> Result<()> {
     }
 
     let response_data = serde_json::from_slice::<EquityPriceResponse>(&response.bytes)?;
-    let price = response_data
+    let quote = response_data
         .quote
         .get(&format!("{symbol}:USLF24"))
-        .and_then(|quote| quote.get("askPrice"))
-        .and_then(|price| price.as_f64())
         .ok_or_else(|| anyhow::anyhow!("Price not found in response"))?;
-    let price_lossless = (price * 1_000_000.0) as u128;
-    log!("Fetched price: {price_lossless:?}");
 
-    // Scaled 1e6 u128, serialized little-endian for tally.
-    Process::success(&price_lossless.to_le_bytes());
+    let bid_price = quote
+        .get("bidPrice")
+        .ok_or_else(|| anyhow::anyhow!("bidPrice not found in response"))
+        .and_then(|price| scaled_u128_from_value(price, PRICE_SCALE_DECIMALS))?;
+    let ask_price = quote
+        .get("askPrice")
+        .ok_or_else(|| anyhow::anyhow!("askPrice not found in response"))
+        .and_then(|price| scaled_u128_from_value(price, PRICE_SCALE_DECIMALS))?;
+    let mid_price = (bid_price + ask_price) / 2;
+
+    // `eventTime` is 0 on this feed in practice; the freshest of the two
+    // quote-side timestamps is the best available signal of when the quote
+    // was struck.
+    let event_time_ms = [
+        quote.get("eventTime").and_then(|t| t.as_u64()),
+        quote.get("bidTime").and_then(|t| t.as_u64()),
+        quote.get("askTime").and_then(|t| t.as_u64()),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+    .unwrap_or(0);
+
+    log!(
+        "Fetched quote: bid={bid_price}, ask={ask_price}, mid={mid_price}, event_time_ms={event_time_ms}"
+    );
+
+    // This is a per-node staleness check only: it catches a frozen feed on
+    // the node that fetched it. A node reporting a quote whose timestamp is
+    // merely inconsistent with the rest of the cohort survives this check;
+    // that's instead rejected cross-reveal in `tally_phase`'s
+    // `filter_timestamp_outliers`.
+    let age_ms = now_ms()?.saturating_sub(event_time_ms);
+    if age_ms > max_staleness_ms {
+        elog!(
+            "Stale quote for {symbol}: age_ms={age_ms} exceeds max_staleness_ms={max_staleness_ms}"
+        );
+        Process::error("Stale quote".as_bytes());
+        return Ok(());
+    }
+
+    let values = vec![
+        U256::from(bid_price),
+        U256::from(ask_price),
+        U256::from(mid_price),
+        U256::from(event_time_ms),
+    ];
+
+    // [bid, ask, mid, eventTime] as int256[], scaled 1e6 for prices / raw ms
+    // for the timestamp. Consumed by this program's own `tally_phase`, which
+    // expects exactly these four fields per reveal rather than seda-core's
+    // `aggregate_each_field` convention of a leading
+    // `[method_tag, method_param, outlier_k]` triple.
+    let encoded = ethabi::encode(&[Token::Array(
+        values.into_iter().map(Token::Int).collect(),
+    )]);
+    Process::success(&encoded);
 
     Ok(())
 }
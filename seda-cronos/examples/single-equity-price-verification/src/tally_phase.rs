@@ -0,0 +1,159 @@
+use anyhow::{Result, anyhow};
+use ethabi::{Token, ethereum_types::U256};
+use seda_sdk_rs::{Process, elog, get_reveals, log};
+
+/// MAD multiplier for rejecting a reveal whose `event_time_ms` is
+/// inconsistent with the rest of the cohort's — on top of (not instead of)
+/// each node's own per-reveal staleness gate in `execution_phase`, which
+/// only catches a frozen feed on the node that fetched it, not a node
+/// reporting a quote struck at a different moment than everyone else's.
+const TIMESTAMP_OUTLIER_K: u64 = 5;
+
+/// Minimum number of reveals that must survive timestamp-consistency
+/// filtering; below this the tally has no meaningful consensus left to
+/// report.
+const MIN_SURVIVORS_AFTER_FILTER: usize = 1;
+
+pub fn tally_phase() -> Result<()> {
+    if let Err(err) = tally_phase_inner() {
+        elog!("Tally error: {err}");
+        Process::error(format!("Tally error: {err}").as_bytes());
+    }
+
+    Ok(())
+}
+
+struct Quote {
+    bid: U256,
+    ask: U256,
+    mid: U256,
+    event_time_ms: U256,
+}
+
+fn tally_phase_inner() -> Result<()> {
+    let reveals = get_reveals()?;
+    let mut quotes = Vec::with_capacity(reveals.len());
+
+    for reveal in reveals {
+        match decode_reveal(&reveal.body.reveal) {
+            Ok(quote) => quotes.push(quote),
+            Err(err) => elog!("Reveal decode failed: {err}"),
+        }
+    }
+
+    if quotes.is_empty() {
+        Process::error("No consensus among revealed results".as_bytes());
+        return Ok(());
+    }
+
+    let survivors = filter_timestamp_outliers(&quotes, TIMESTAMP_OUTLIER_K);
+    if survivors.len() < MIN_SURVIVORS_AFTER_FILTER {
+        return Err(anyhow!(
+            "Only {} of {} reveals survived timestamp-consistency filtering",
+            survivors.len(),
+            quotes.len()
+        ));
+    }
+
+    let bid = median(&survivors.iter().map(|q| q.bid).collect::<Vec<_>>());
+    let ask = median(&survivors.iter().map(|q| q.ask).collect::<Vec<_>>());
+    let mid = median(&survivors.iter().map(|q| q.mid).collect::<Vec<_>>());
+    let event_time_ms = median(&survivors.iter().map(|q| q.event_time_ms).collect::<Vec<_>>());
+    log!("Final aggregated quote: bid={bid}, ask={ask}, mid={mid}, event_time_ms={event_time_ms}");
+
+    let result = ethabi::encode(&[Token::Array(vec![
+        Token::Int(bid),
+        Token::Int(ask),
+        Token::Int(mid),
+        Token::Int(event_time_ms),
+    ])]);
+    Process::success(&result);
+
+    Ok(())
+}
+
+/// Every reveal is the `[bid, ask, mid, eventTime]` int256[] produced by
+/// `execution_phase`.
+fn decode_reveal(bytes: &[u8]) -> Result<Quote> {
+    let tokens = ethabi::decode(
+        &[ethabi::ParamType::Array(Box::new(ethabi::ParamType::Int(
+            256,
+        )))],
+        bytes,
+    )?;
+    let array = match tokens.first() {
+        Some(Token::Array(values)) => values,
+        _ => return Err(anyhow!("Expected array token")),
+    };
+    if array.len() != 4 {
+        return Err(anyhow!(
+            "Expected 4 values (bid, ask, mid, eventTime), got {}",
+            array.len()
+        ));
+    }
+
+    let mut values = Vec::with_capacity(4);
+    for token in array {
+        match token {
+            Token::Int(value) => values.push(*value),
+            _ => return Err(anyhow!("Expected int256 token")),
+        }
+    }
+
+    Ok(Quote {
+        bid: values[0],
+        ask: values[1],
+        mid: values[2],
+        event_time_ms: values[3],
+    })
+}
+
+/// Rejects whole reveals whose `event_time_ms` is more than `k`
+/// median-absolute-deviations from the cohort's median timestamp, so a node
+/// reporting a quote struck far earlier or later than everyone else's can't
+/// drag the aggregate with a price quoted at a different moment. `k == 0`
+/// disables the filter; a `MAD` of zero (the honest majority agrees
+/// exactly) also disables it, since there's no meaningful spread to measure
+/// outliers against.
+fn filter_timestamp_outliers(quotes: &[Quote], k: u64) -> Vec<&Quote> {
+    if k == 0 || quotes.len() < 3 {
+        return quotes.iter().collect();
+    }
+
+    let times: Vec<U256> = quotes.iter().map(|q| q.event_time_ms).collect();
+    let med = median(&times);
+
+    let mut deviations: Vec<U256> = times.iter().map(|t| abs_diff(*t, med)).collect();
+    deviations.sort();
+    let mad = median(&deviations);
+    if mad.is_zero() {
+        return quotes.iter().collect();
+    }
+
+    let threshold = mad.saturating_mul(U256::from(k));
+    let survivors: Vec<&Quote> = quotes
+        .iter()
+        .filter(|q| abs_diff(q.event_time_ms, med) <= threshold)
+        .collect();
+
+    let rejected = quotes.len() - survivors.len();
+    if rejected > 0 {
+        log!("Rejected {rejected} reveal(s) with inconsistent event_time_ms (k={k}, MAD={mad})");
+    }
+    survivors
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b { a - b } else { b - a }
+}
+
+fn median(values: &[U256]) -> U256 {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / U256::from(2u8)
+    } else {
+        sorted[mid]
+    }
+}
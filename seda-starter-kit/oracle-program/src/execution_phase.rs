@@ -1,4 +1,5 @@
 use anyhow::{Result, anyhow};
+use ethabi::ethereum_types::U256;
 use seda_sdk_rs::{
     Process,
     elog,
@@ -7,11 +8,24 @@ use seda_sdk_rs::{
     http::{HttpFetchMethod, HttpFetchOptions},
     bytes::ToBytes,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha3::{Digest, Keccak256};
 
 const RPC_URL: &str =
     "https://mainnet-sticky.cronoslabs.com/v1/d3642384d334ff6ff1c4baebfdf3ef7d";
+
+/// Default RPC endpoints queried when no explicit `rpc_url` override is
+/// given in the input. A single compromised or lagging provider can't move
+/// the result on its own: at least [`RPC_QUORUM`] of them must return
+/// byte-identical results for the same call before it's trusted.
+const RPC_URLS: &[&str] = &[
+    RPC_URL,
+    "https://cronos.blockpi.network/v1/rpc/0467a344ecda6f87cc7118bd02a14f5818a2f5ff",
+    "https://evm.cronos.org",
+];
+const RPC_QUORUM: usize = 2;
+
 const FACTORY_ADDRESS: &str = "0x3b44b2a187a7b3824131f8db5a74194d0a42fc15";
 const WCRO_ADDRESS: &str = "0x5C7F8A570d578ED84E63fdFA7b1eE72dEae1AE23";
 const USDC_ADDRESS: &str = "0xc21223249CA28397B4B6541dfFaEcC539BfF0c59";
@@ -20,6 +34,14 @@ const SELECTOR_GET_PAIR: &str = "e6a43905";
 const SELECTOR_GET_RESERVES: &str = "0902f1ac";
 const SELECTOR_TOKEN0: &str = "0dfe1681";
 
+const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+const SELECTOR_AGGREGATE3: &str = "82ad56cb";
+
+// UniswapV2Pair storage layout: token0 lives at slot 6, and reserve0/reserve1/
+// blockTimestampLast are packed into slot 8.
+const TOKEN0_SLOT: u64 = 6;
+const RESERVES_SLOT: u64 = 8;
+
 #[derive(Serialize)]
 struct RpcPayload {
     pair_address: String,
@@ -27,20 +49,22 @@ struct RpcPayload {
     reserves_result: String,
 }
 
-pub fn execution_phase() -> Result<()> {
-    #[cfg(not(feature = "test"))]
-    if Process::replication_factor() != 1 {
-        elog!("Replication factor must be 1 for this oracle program.");
-        Process::error("Invalid replication factor".as_bytes());
-        return Ok(());
-    }
+#[derive(Deserialize, Default)]
+struct ExecutionInput {
+    rpc_url: Option<String>,
+    #[serde(default)]
+    verify: bool,
+}
 
+pub fn execution_phase() -> Result<()> {
     let inputs = String::from_utf8(Process::get_inputs())?;
-    let rpc_url = if inputs.trim().is_empty() {
-        RPC_URL.to_string()
-    } else {
-        inputs.trim().to_string()
+    let input = parse_input(&inputs)?;
+    let rpc_urls: Vec<&str> = match &input.rpc_url {
+        Some(url) => vec![url.as_str()],
+        None => RPC_URLS.to_vec(),
     };
+    // A single caller-supplied endpoint can only ever agree with itself.
+    let quorum = RPC_QUORUM.min(rpc_urls.len());
 
     log!("Fetching VVS WCRO/USDC pool from Cronos RPC");
 
@@ -49,7 +73,7 @@ pub fn execution_phase() -> Result<()> {
         WCRO_ADDRESS,
         USDC_ADDRESS,
     );
-    let pair_result = rpc_call(&rpc_url, FACTORY_ADDRESS, &get_pair_data)?;
+    let pair_result = rpc_call_quorum(&rpc_urls, quorum, FACTORY_ADDRESS, &get_pair_data)?;
     let pair_address = parse_address_from_32byte(&pair_result)
         .ok_or_else(|| anyhow!("Failed to parse pair address"))?;
 
@@ -59,11 +83,26 @@ pub fn execution_phase() -> Result<()> {
         return Ok(());
     }
 
-    let token0_result = rpc_call(&rpc_url, &pair_address, SELECTOR_TOKEN0)?;
-    let token0 = parse_address_from_32byte(&token0_result)
-        .ok_or_else(|| anyhow!("Failed to parse token0 address"))?;
-
-    let reserves_result = rpc_call(&rpc_url, &pair_address, SELECTOR_GET_RESERVES)?;
+    let (token0, reserves_result) = if input.verify {
+        let rpc_url = rpc_urls
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("No RPC endpoint available for verification"))?;
+        log!("Verifying {pair_address} reserves via eth_getProof against stateRoot");
+        fetch_reserves_verified(rpc_url, &pair_address)?
+    } else {
+        let results = multicall_quorum(
+            &rpc_urls,
+            quorum,
+            &[
+                (pair_address.as_str(), SELECTOR_TOKEN0),
+                (pair_address.as_str(), SELECTOR_GET_RESERVES),
+            ],
+        )?;
+        let token0 = parse_address_from_32byte(&results[0])
+            .ok_or_else(|| anyhow!("Failed to parse token0 address"))?;
+        (token0, results[1].clone())
+    };
 
     let payload = RpcPayload {
         pair_address,
@@ -77,6 +116,53 @@ pub fn execution_phase() -> Result<()> {
     Ok(())
 }
 
+fn parse_input(raw: &str) -> Result<ExecutionInput> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(ExecutionInput::default());
+    }
+
+    if let Ok(input) = serde_json::from_str::<ExecutionInput>(trimmed) {
+        return Ok(input);
+    }
+
+    // Backward-compatible path: a bare string is an RPC URL override.
+    Ok(ExecutionInput {
+        rpc_url: Some(trimmed.to_string()),
+        verify: false,
+    })
+}
+
+/// Fetches `token0` and the packed reserves for `pair_address` via
+/// `eth_getProof` and verifies both storage slots against the latest block's
+/// `stateRoot`, rather than trusting a plain `eth_call`. Returns the token0
+/// address and a 64-byte hex payload of `reserve0` and `reserve1` as 32-byte
+/// words (the leading part of the `getReserves()` shape, without
+/// `blockTimestampLast`), which is all the tally phase parses.
+fn fetch_reserves_verified(rpc_url: &str, pair_address: &str) -> Result<(String, String)> {
+    let (block_hash, state_root) = rpc_get_block_header(rpc_url)?;
+    let slots = [U256::from(TOKEN0_SLOT), U256::from(RESERVES_SLOT)];
+    let proof = rpc_get_proof(rpc_url, pair_address, &slots, &block_hash)?;
+
+    let account_leaf = verify_account_proof(pair_address, &proof.account_proof, state_root)?;
+    let storage_root = account_storage_root(&account_leaf)?;
+
+    let token0_value = verify_storage_slot(U256::from(TOKEN0_SLOT), &proof, storage_root)?;
+    let token0 = format!("0x{:040x}", token0_value);
+
+    let reserves_value = verify_storage_slot(U256::from(RESERVES_SLOT), &proof, storage_root)?;
+    let mask_112 = (U256::one() << 112) - U256::one();
+    let reserve0 = reserves_value & mask_112;
+    let reserve1 = (reserves_value >> 112) & mask_112;
+
+    let mut reserves_bytes = [0u8; 64];
+    reserve0.to_big_endian(&mut reserves_bytes[0..32]);
+    reserve1.to_big_endian(&mut reserves_bytes[32..64]);
+    let reserves_result = format!("0x{}", hex_encode(&reserves_bytes));
+
+    Ok((token0, reserves_result))
+}
+
 fn rpc_call(rpc_url: &str, to: &str, data: &str) -> Result<String> {
     let body = json!({
         "jsonrpc": "2.0",
@@ -91,6 +177,225 @@ fn rpc_call(rpc_url: &str, to: &str, data: &str) -> Result<String> {
         ]
     });
 
+    let json_value = rpc_request(rpc_url, body)?;
+    let result = json_value
+        .get("result")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| anyhow!("RPC response missing result"))?;
+
+    Ok(result.to_string())
+}
+
+/// Queries the same `eth_call` against every endpoint in `rpc_urls` and
+/// requires at least `quorum` of them to return a byte-identical result
+/// before trusting it, so a single lying or unreachable RPC provider can't
+/// move the reported pool data on its own.
+fn rpc_call_quorum(rpc_urls: &[&str], quorum: usize, to: &str, data: &str) -> Result<String> {
+    let mut results = Vec::with_capacity(rpc_urls.len());
+    for &rpc_url in rpc_urls {
+        match rpc_call(rpc_url, to, data) {
+            Ok(result) => results.push(result),
+            Err(err) => elog!("RPC endpoint {rpc_url} failed: {err}"),
+        }
+    }
+    agreeing_result(&results, quorum)
+}
+
+/// Runs [`multicall`] against every endpoint in `rpc_urls` and requires at
+/// least `quorum` of them to agree on the whole batch of results before
+/// trusting it.
+fn multicall_quorum(
+    rpc_urls: &[&str],
+    quorum: usize,
+    calls: &[(&str, &str)],
+) -> Result<Vec<String>> {
+    let mut batches = Vec::with_capacity(rpc_urls.len());
+    for &rpc_url in rpc_urls {
+        match multicall(rpc_url, calls) {
+            Ok(batch) => batches.push(batch),
+            Err(err) => elog!("RPC endpoint {rpc_url} failed: {err}"),
+        }
+    }
+    agreeing_result(&batches, quorum)
+}
+
+/// Returns the first value in `results` that at least `quorum` entries are
+/// equal to.
+fn agreeing_result<T: PartialEq + Clone>(results: &[T], quorum: usize) -> Result<T> {
+    for candidate in results {
+        let count = results.iter().filter(|r| *r == candidate).count();
+        if count >= quorum {
+            return Ok(candidate.clone());
+        }
+    }
+    Err(anyhow!(
+        "No quorum of {quorum} RPC endpoints agreed (got {} usable responses)",
+        results.len()
+    ))
+}
+
+/// Batches `calls` into a single `aggregate3()` round-trip against
+/// Multicall3 instead of issuing one `eth_call` per `(to, data)` pair.
+/// Falls back to sequential `rpc_call`s if the multicall itself fails or a
+/// sub-call reverts, so chains without Multicall3 deployed still work.
+fn multicall(rpc_url: &str, calls: &[(&str, &str)]) -> Result<Vec<String>> {
+    match multicall_inner(rpc_url, calls) {
+        Ok(results) => Ok(results),
+        Err(err) => {
+            elog!("Multicall3 aggregate3 failed, falling back to sequential eth_call: {err}");
+            calls
+                .iter()
+                .map(|(to, data)| rpc_call(rpc_url, to, data))
+                .collect()
+        }
+    }
+}
+
+fn multicall_inner(rpc_url: &str, calls: &[(&str, &str)]) -> Result<Vec<String>> {
+    let data = encode_aggregate3(calls)?;
+    let result_hex = rpc_call(rpc_url, MULTICALL3_ADDRESS, &data)?;
+    let result_bytes =
+        hex_to_bytes(&result_hex).ok_or_else(|| anyhow!("Failed to parse aggregate3 hex"))?;
+    let results = decode_aggregate3_result(&result_bytes)?;
+    if results.len() != calls.len() {
+        return Err(anyhow!("aggregate3 returned {} results, expected {}", results.len(), calls.len()));
+    }
+
+    results
+        .into_iter()
+        .zip(calls)
+        .map(|((success, return_data), (to, _))| {
+            if !success {
+                return Err(anyhow!("aggregate3 sub-call to {to} reverted"));
+            }
+            Ok(format!("0x{}", hex_encode(&return_data)))
+        })
+        .collect()
+}
+
+/// ABI-encodes `aggregate3((address,bool,bytes)[])` for `calls`, marking
+/// every sub-call as `allowFailure = true` so one bad RPC target doesn't
+/// revert the whole batch.
+fn encode_aggregate3(calls: &[(&str, &str)]) -> Result<String> {
+    let mut tuples = Vec::with_capacity(calls.len());
+    for (to, data) in calls {
+        let call_data = hex_to_bytes(data).ok_or_else(|| anyhow!("invalid calldata hex"))?;
+        let mut tuple = Vec::new();
+        tuple.extend(abi_encode_address(to)?);
+        tuple.extend(abi_encode_bool(true));
+        tuple.extend(abi_encode_uint(U256::from(3 * 32))); // offset to bytes, within the tuple
+        tuple.extend(abi_encode_bytes(&call_data));
+        tuples.push(tuple);
+    }
+
+    let heads_len = 32 * tuples.len();
+    let mut offsets = Vec::with_capacity(tuples.len());
+    let mut elements = Vec::new();
+    let mut running_offset = heads_len;
+    for tuple in &tuples {
+        offsets.push(abi_encode_uint(U256::from(running_offset)));
+        running_offset += tuple.len();
+        elements.extend_from_slice(tuple);
+    }
+
+    let mut body = abi_encode_uint(U256::from(tuples.len())).to_vec();
+    for offset in offsets {
+        body.extend(offset);
+    }
+    body.extend(elements);
+
+    let mut encoded = hex_encode(&abi_encode_uint(U256::from(0x20u8))).to_string();
+    encoded.push_str(&hex_encode(&body));
+    Ok(format!("{SELECTOR_AGGREGATE3}{encoded}"))
+}
+
+/// Decodes the `(bool success, bytes returnData)[]` return value of
+/// `aggregate3()`.
+fn decode_aggregate3_result(bytes: &[u8]) -> Result<Vec<(bool, Vec<u8>)>> {
+    let array_offset = u256_to_usize(u256_from_be_slice(
+        bytes.get(0..32).ok_or_else(|| anyhow!("aggregate3 result truncated"))?,
+    ))?;
+    let length = u256_to_usize(u256_from_be_slice(
+        bytes
+            .get(array_offset..array_offset + 32)
+            .ok_or_else(|| anyhow!("aggregate3 result truncated (length)"))?,
+    ))?;
+
+    let elements_start = array_offset + 32;
+    let mut results = Vec::with_capacity(length);
+    for i in 0..length {
+        let offset_slot = bytes
+            .get(elements_start + i * 32..elements_start + (i + 1) * 32)
+            .ok_or_else(|| anyhow!("aggregate3 result truncated (element offset)"))?;
+        let tuple_start = elements_start + u256_to_usize(u256_from_be_slice(offset_slot))?;
+
+        let success = bytes
+            .get(tuple_start..tuple_start + 32)
+            .ok_or_else(|| anyhow!("aggregate3 result truncated (success)"))?
+            .iter()
+            .any(|&b| b != 0);
+        let bytes_offset = u256_to_usize(u256_from_be_slice(
+            bytes
+                .get(tuple_start + 32..tuple_start + 64)
+                .ok_or_else(|| anyhow!("aggregate3 result truncated (bytes offset)"))?,
+        ))?;
+        let bytes_start = tuple_start + bytes_offset;
+        let return_len = u256_to_usize(u256_from_be_slice(
+            bytes
+                .get(bytes_start..bytes_start + 32)
+                .ok_or_else(|| anyhow!("aggregate3 result truncated (returnData length)"))?,
+        ))?;
+        let return_data = bytes
+            .get(bytes_start + 32..bytes_start + 32 + return_len)
+            .ok_or_else(|| anyhow!("aggregate3 result truncated (returnData)"))?
+            .to_vec();
+
+        results.push((success, return_data));
+    }
+
+    Ok(results)
+}
+
+fn abi_encode_uint(value: U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    value.to_big_endian(&mut out);
+    out
+}
+
+fn abi_encode_bool(value: bool) -> [u8; 32] {
+    abi_encode_uint(U256::from(value as u8))
+}
+
+fn abi_encode_address(address: &str) -> Result<[u8; 32]> {
+    let bytes = hex_to_bytes(address).ok_or_else(|| anyhow!("invalid address hex"))?;
+    if bytes.len() != 20 {
+        return Err(anyhow!("address must be 20 bytes"));
+    }
+    let mut out = [0u8; 32];
+    out[12..].copy_from_slice(&bytes);
+    Ok(out)
+}
+
+fn abi_encode_bytes(data: &[u8]) -> Vec<u8> {
+    let mut out = abi_encode_uint(U256::from(data.len())).to_vec();
+    out.extend_from_slice(data);
+    let padding = (32 - (data.len() % 32)) % 32;
+    out.extend(std::iter::repeat(0u8).take(padding));
+    out
+}
+
+fn u256_to_usize(value: U256) -> Result<usize> {
+    if value > U256::from(usize::MAX) {
+        return Err(anyhow!("value does not fit in usize"));
+    }
+    Ok(value.as_u64() as usize)
+}
+
+fn u256_from_be_slice(slice: &[u8]) -> U256 {
+    U256::from_big_endian(slice)
+}
+
+fn rpc_request(rpc_url: &str, body: serde_json::Value) -> Result<serde_json::Value> {
     let body_bytes = serde_json::to_vec(&body)?;
     let mut headers = std::collections::BTreeMap::new();
     headers.insert("Content-Type".to_string(), "application/json".to_string());
@@ -113,12 +418,366 @@ fn rpc_call(rpc_url: &str, to: &str, data: &str) -> Result<String> {
     }
 
     let json_value: serde_json::Value = serde_json::from_slice(&response.bytes)?;
+    if let Some(error) = json_value.get("error") {
+        return Err(anyhow!("RPC error: {error}"));
+    }
+    Ok(json_value)
+}
+
+/// Latest block's hash and `stateRoot`, used as the trust anchor for
+/// `eth_getProof` verification.
+fn rpc_get_block_header(rpc_url: &str) -> Result<(String, [u8; 32])> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getBlockByNumber",
+        "params": ["latest", false]
+    });
+    let json_value = rpc_request(rpc_url, body)?;
+    let block = json_value
+        .get("result")
+        .ok_or_else(|| anyhow!("RPC response missing block"))?;
+
+    let hash = block
+        .get("hash")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Block missing hash"))?
+        .to_string();
+    let state_root_hex = block
+        .get("stateRoot")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Block missing stateRoot"))?;
+    let state_root_bytes =
+        hex_to_bytes(state_root_hex).ok_or_else(|| anyhow!("Invalid stateRoot hex"))?;
+    if state_root_bytes.len() != 32 {
+        return Err(anyhow!("stateRoot is not 32 bytes"));
+    }
+    let mut state_root = [0u8; 32];
+    state_root.copy_from_slice(&state_root_bytes);
+
+    Ok((hash, state_root))
+}
+
+struct EthProof {
+    account_proof: Vec<Vec<u8>>,
+    storage_proofs: Vec<(U256, Vec<Vec<u8>>)>,
+}
+
+fn rpc_get_proof(
+    rpc_url: &str,
+    address: &str,
+    slots: &[U256],
+    block_hash: &str,
+) -> Result<EthProof> {
+    let keys: Vec<String> = slots.iter().map(|slot| format!("0x{slot:x}")).collect();
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getProof",
+        "params": [address, keys, block_hash]
+    });
+    let json_value = rpc_request(rpc_url, body)?;
     let result = json_value
         .get("result")
-        .and_then(|value| value.as_str())
-        .ok_or_else(|| anyhow!("RPC response missing result"))?;
+        .ok_or_else(|| anyhow!("RPC response missing proof result"))?;
 
-    Ok(result.to_string())
+    let account_proof = decode_hex_array(
+        result
+            .get("accountProof")
+            .ok_or_else(|| anyhow!("Proof missing accountProof"))?,
+    )?;
+
+    let storage_proof_json = result
+        .get("storageProof")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow!("Proof missing storageProof"))?;
+    if storage_proof_json.len() != slots.len() {
+        return Err(anyhow!("storageProof length mismatch"));
+    }
+
+    let mut storage_proofs = Vec::with_capacity(slots.len());
+    for (slot, entry) in slots.iter().zip(storage_proof_json) {
+        let proof = decode_hex_array(
+            entry
+                .get("proof")
+                .ok_or_else(|| anyhow!("storageProof entry missing proof"))?,
+        )?;
+        storage_proofs.push((*slot, proof));
+    }
+
+    Ok(EthProof {
+        account_proof,
+        storage_proofs,
+    })
+}
+
+fn decode_hex_array(value: &serde_json::Value) -> Result<Vec<Vec<u8>>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("expected a JSON array of hex strings"))?
+        .iter()
+        .map(|entry| {
+            entry
+                .as_str()
+                .and_then(hex_to_bytes)
+                .ok_or_else(|| anyhow!("invalid proof node hex"))
+        })
+        .collect()
+}
+
+/// Verifies the account proof for `address` against `state_root` and
+/// returns the account's RLP-decoded leaf value
+/// (`[nonce, balance, storageRoot, codeHash]`).
+fn verify_account_proof(
+    address: &str,
+    account_proof: &[Vec<u8>],
+    state_root: [u8; 32],
+) -> Result<Vec<RlpItem>> {
+    let address_bytes = hex_to_bytes(address).ok_or_else(|| anyhow!("invalid address hex"))?;
+    if address_bytes.len() != 20 {
+        return Err(anyhow!("address must be 20 bytes"));
+    }
+    let key = keccak256(&address_bytes);
+    let leaf_bytes = verify_trie_proof(&key, account_proof, state_root)?;
+    let account = rlp_as_list(&rlp_decode(&leaf_bytes)?)?.to_vec();
+    if account.len() != 4 {
+        return Err(anyhow!("malformed account RLP (expected 4 fields)"));
+    }
+    Ok(account)
+}
+
+fn account_storage_root(account: &[RlpItem]) -> Result<[u8; 32]> {
+    let storage_root_bytes = rlp_as_bytes(&account[2])?;
+    if storage_root_bytes.len() != 32 {
+        return Err(anyhow!("storageRoot is not 32 bytes"));
+    }
+    let mut storage_root = [0u8; 32];
+    storage_root.copy_from_slice(storage_root_bytes);
+    Ok(storage_root)
+}
+
+fn verify_storage_slot(slot: U256, proof: &EthProof, storage_root: [u8; 32]) -> Result<U256> {
+    let (_, storage_proof) = proof
+        .storage_proofs
+        .iter()
+        .find(|(s, _)| *s == slot)
+        .ok_or_else(|| anyhow!("missing storage proof for slot {slot}"))?;
+
+    let mut slot_bytes = [0u8; 32];
+    slot.to_big_endian(&mut slot_bytes);
+    let key = keccak256(&slot_bytes);
+
+    let value_bytes = verify_trie_proof(&key, storage_proof, storage_root)?;
+    let encoded_value = rlp_as_bytes(&rlp_decode(&value_bytes)?)?;
+    Ok(U256::from_big_endian(&{
+        let mut padded = [0u8; 32];
+        let start = 32usize.saturating_sub(encoded_value.len());
+        padded[start..].copy_from_slice(encoded_value);
+        padded
+    }))
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&hasher.finalize());
+    out
+}
+
+/// Minimal RLP item: either a byte string or a list of items.
+#[derive(Clone)]
+enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+fn rlp_as_bytes(item: &RlpItem) -> Result<&[u8]> {
+    match item {
+        RlpItem::Bytes(bytes) => Ok(bytes),
+        RlpItem::List(_) => Err(anyhow!("expected RLP bytes, got a list")),
+    }
+}
+
+fn rlp_as_list(item: &RlpItem) -> Result<&[RlpItem]> {
+    match item {
+        RlpItem::List(items) => Ok(items),
+        RlpItem::Bytes(_) => Err(anyhow!("expected an RLP list, got bytes")),
+    }
+}
+
+fn rlp_decode(input: &[u8]) -> Result<RlpItem> {
+    let (item, consumed) = rlp_decode_one(input)?;
+    if consumed != input.len() {
+        return Err(anyhow!("trailing bytes after RLP item"));
+    }
+    Ok(item)
+}
+
+fn rlp_decode_one(input: &[u8]) -> Result<(RlpItem, usize)> {
+    let prefix = *input.first().ok_or_else(|| anyhow!("truncated RLP input"))?;
+    match prefix {
+        0x00..=0x7f => Ok((RlpItem::Bytes(vec![prefix]), 1)),
+        0x80..=0xb7 => {
+            let len = (prefix - 0x80) as usize;
+            let data = input
+                .get(1..1 + len)
+                .ok_or_else(|| anyhow!("truncated RLP short string"))?;
+            Ok((RlpItem::Bytes(data.to_vec()), 1 + len))
+        }
+        0xb8..=0xbf => {
+            let len_of_len = (prefix - 0xb7) as usize;
+            let len = rlp_be_len(input, 1, len_of_len)?;
+            let start = 1 + len_of_len;
+            let data = input
+                .get(start..start + len)
+                .ok_or_else(|| anyhow!("truncated RLP long string"))?;
+            Ok((RlpItem::Bytes(data.to_vec()), start + len))
+        }
+        0xc0..=0xf7 => {
+            let len = (prefix - 0xc0) as usize;
+            let body = input
+                .get(1..1 + len)
+                .ok_or_else(|| anyhow!("truncated RLP short list"))?;
+            Ok((RlpItem::List(rlp_decode_list_body(body)?), 1 + len))
+        }
+        0xf8..=0xff => {
+            let len_of_len = (prefix - 0xf7) as usize;
+            let len = rlp_be_len(input, 1, len_of_len)?;
+            let start = 1 + len_of_len;
+            let body = input
+                .get(start..start + len)
+                .ok_or_else(|| anyhow!("truncated RLP long list"))?;
+            Ok((RlpItem::List(rlp_decode_list_body(body)?), start + len))
+        }
+    }
+}
+
+fn rlp_decode_list_body(mut body: &[u8]) -> Result<Vec<RlpItem>> {
+    let mut items = Vec::new();
+    while !body.is_empty() {
+        let (item, consumed) = rlp_decode_one(body)?;
+        items.push(item);
+        body = &body[consumed..];
+    }
+    Ok(items)
+}
+
+fn rlp_be_len(input: &[u8], start: usize, len_of_len: usize) -> Result<usize> {
+    let len_bytes = input
+        .get(start..start + len_of_len)
+        .ok_or_else(|| anyhow!("truncated RLP length prefix"))?;
+    if len_bytes.len() > std::mem::size_of::<usize>() {
+        return Err(anyhow!("RLP length prefix too large"));
+    }
+    Ok(len_bytes
+        .iter()
+        .fold(0usize, |acc, byte| (acc << 8) | *byte as usize))
+}
+
+fn bytes_to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push(byte >> 4);
+        out.push(byte & 0x0f);
+    }
+    out
+}
+
+/// Decodes a hex-prefix encoded path (used by extension/leaf nodes), returning
+/// the path nibbles and whether the node is a leaf.
+fn hex_prefix_decode(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let first = *encoded
+        .first()
+        .ok_or_else(|| anyhow!("empty hex-prefix encoded path"))?;
+    let flag = first >> 4;
+    let is_leaf = flag == 2 || flag == 3;
+    let is_odd = flag == 1 || flag == 3;
+
+    let mut path = Vec::new();
+    if is_odd {
+        path.push(first & 0x0f);
+    }
+    path.extend(bytes_to_nibbles(&encoded[1..]));
+    Ok((path, is_leaf))
+}
+
+/// A branch/extension child reference, per the MPT spec: a 32-byte keccak
+/// hash looked up in the proof list, or — when the child's own RLP encoding
+/// is under 32 bytes — the child node embedded directly in its parent.
+enum TrieChild {
+    Hashed([u8; 32]),
+    Embedded(Vec<RlpItem>),
+}
+
+fn decode_trie_child(item: RlpItem) -> Result<TrieChild> {
+    match item {
+        RlpItem::List(items) => Ok(TrieChild::Embedded(items)),
+        RlpItem::Bytes(bytes) => {
+            if bytes.is_empty() {
+                return Err(anyhow!("proof terminates at an empty branch slot"));
+            }
+            if bytes.len() != 32 {
+                return Err(anyhow!("branch child must reference a 32-byte hash"));
+            }
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&bytes);
+            Ok(TrieChild::Hashed(hash))
+        }
+    }
+}
+
+/// Walks a Merkle-Patricia trie proof for `key` starting at `root`, verifying
+/// that each hashed node's `keccak256` matches the hash referenced by its
+/// parent (an embedded child, inlined because its own RLP is under 32 bytes,
+/// needs no such check — it isn't hashed in the first place), and returns the
+/// RLP-encoded value at the leaf.
+fn verify_trie_proof(key: &[u8; 32], proof: &[Vec<u8>], root: [u8; 32]) -> Result<Vec<u8>> {
+    let mut path = bytes_to_nibbles(key);
+    let mut next = TrieChild::Hashed(root);
+    let mut proof_nodes = proof.iter();
+
+    loop {
+        let node = match next {
+            TrieChild::Hashed(expected_hash) => {
+                let node_bytes = proof_nodes
+                    .next()
+                    .ok_or_else(|| anyhow!("proof exhausted before reaching a leaf"))?;
+                if keccak256(node_bytes) != expected_hash {
+                    return Err(anyhow!("proof node hash does not match expected root"));
+                }
+                rlp_as_list(&rlp_decode(node_bytes)?)?.to_vec()
+            }
+            TrieChild::Embedded(items) => items,
+        };
+
+        match node.len() {
+            17 => {
+                if path.is_empty() {
+                    return Ok(rlp_as_bytes(&node[16])?.to_vec());
+                }
+                let nibble = path.remove(0) as usize;
+                let mut node = node;
+                next = decode_trie_child(node.swap_remove(nibble))?;
+            }
+            2 => {
+                let (segment, is_leaf) = hex_prefix_decode(rlp_as_bytes(&node[0])?)?;
+                if path.len() < segment.len() || path[..segment.len()] != segment[..] {
+                    return Err(anyhow!("proof path does not match requested key"));
+                }
+                path.drain(0..segment.len());
+                if is_leaf {
+                    if !path.is_empty() {
+                        return Err(anyhow!("leaf node reached with unconsumed path"));
+                    }
+                    return Ok(rlp_as_bytes(&node[1])?.to_vec());
+                }
+                let mut node = node;
+                next = decode_trie_child(node.swap_remove(1))?;
+            }
+            other => return Err(anyhow!("unexpected trie node arity {other}")),
+        }
+    }
 }
 
 fn encode_call_with_two_addresses(selector: &str, a: &str, b: &str) -> String {
@@ -147,3 +806,18 @@ fn strip_0x(value: &str) -> &str {
 fn is_zero_address(address: &str) -> bool {
     strip_0x(address).chars().all(|c| c == '0')
 }
+
+fn hex_to_bytes(value: &str) -> Option<Vec<u8>> {
+    let cleaned = strip_0x(value);
+    if cleaned.len() % 2 != 0 {
+        return None;
+    }
+    (0..cleaned.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&cleaned[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -1,126 +1,224 @@
-use anyhow::Result;
-use ethabi::{Token, ethereum_types::U256};
-use seda_sdk_rs::{HttpFetchResponse, Process, elog, get_unfiltered_reveals, log};
+use anyhow::{Result, anyhow};
+use ethabi::ethereum_types::U256;
+use seda_sdk_rs::{Process, elog, get_unfiltered_reveals, log};
 use serde::Deserialize;
-use std::collections::HashMap;
 
-const TARGET_BASE_SYMBOL: &str = "WCRO";
-const TARGET_QUOTE_SYMBOL: &str = "USDC";
-const PRICE_DECIMALS: u32 = 8;
-const LIQUIDITY_DECIMALS: u32 = 18;
+/// Wire format version for [`encode_reveal`]; bump when the record layout
+/// changes so a decoder can reject a reveal it no longer knows how to read
+/// instead of silently misinterpreting it.
+const REVEAL_WIRE_VERSION: u8 = 1;
+
+/// Oracle asset kind carried in the reveal's `asset_code` byte, so a decoder
+/// can tell which kind of price this is without guessing from which program
+/// produced it. This tally only ever reports the WCRO/USDC pair.
+///
+/// Discriminants start at 16 to stay disjoint from `generic-dxfeed` and
+/// `single-commodity-price`'s shared `AssetKind` (codes 0-5): those two
+/// programs and this tally all emit the same
+/// `[version][asset_code][price_scale][price: u128 LE]...` header, and a
+/// decoder reading `asset_code` across all three needs each value to mean
+/// exactly one thing.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetKind {
+    DexPair = 16,
+}
 
-#[derive(Deserialize)]
-struct VvsPairsResponse {
-    data: HashMap<String, VvsPair>,
+impl TryFrom<u8> for AssetKind {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u8) -> Result<Self> {
+        match code {
+            16 => Ok(AssetKind::DexPair),
+            other => Err(anyhow::anyhow!("Unknown asset kind code: {other}")),
+        }
+    }
+}
+
+/// Compact, self-describing reveal: `[version: u8][asset_code: u8]
+/// [price_scale: u8][price: u128 LE]`, replacing the bare ABI-encoded
+/// `uint256` this tally used to emit so a decoder can identify the asset
+/// kind and scale without assuming them out-of-band.
+const REVEAL_LEN: usize = 1 + 1 + 1 + 16;
+
+fn encode_reveal(kind: AssetKind, price_scale: u8, price: u128) -> [u8; REVEAL_LEN] {
+    let mut reveal = [0u8; REVEAL_LEN];
+    reveal[0] = REVEAL_WIRE_VERSION;
+    reveal[1] = kind as u8;
+    reveal[2] = price_scale;
+    reveal[3..19].copy_from_slice(&price.to_le_bytes());
+    reveal
 }
 
+const WCRO_ADDRESS: &str = "0x5C7F8A570d578ED84E63fdFA7b1eE72dEae1AE23";
+const USDC_ADDRESS: &str = "0xc21223249CA28397B4B6541dfFaEcC539BfF0c59";
+const WCRO_DECIMALS: u32 = 18;
+const USDC_DECIMALS: u32 = 6;
+const PRICE_DECIMALS: u32 = 8;
+
+/// MAD multiplier: samples more than `OUTLIER_K` scaled median-absolute-
+/// deviations from the median are rejected before the final median is taken.
+const OUTLIER_K: u128 = 3;
+/// Fixed-point (numerator/denominator) approximation of the 1.4826 constant
+/// that rescales MAD to be comparable to a standard deviation under a normal
+/// distribution, since prices here are plain `u128` rather than floats.
+const MAD_SCALE_NUM: u128 = 14826;
+const MAD_SCALE_DEN: u128 = 10000;
+/// Below this many survivors, outlier rejection has left no meaningful
+/// consensus to report.
+const MIN_SURVIVORS: usize = 1;
+
+/// The on-chain payload `execution_phase` reveals: the WCRO/USDC pair it
+/// resolved from the factory, that pair's `token0`, and the reserves hex:
+/// either a plain 96-byte `getReserves()` `eth_call` return, or a 64-byte
+/// `eth_getProof`-verified `[reserve0, reserve1]`. Only the first 64 bytes
+/// are read.
 #[derive(Deserialize)]
-struct VvsPair {
-    base_symbol: String,
-    quote_symbol: String,
-    price: String,
-    liquidity: String,
+struct RpcPayload {
+    #[allow(dead_code)]
+    pair_address: String,
+    token0: String,
+    reserves_result: String,
 }
 
 pub fn tally_phase() -> Result<()> {
     let reveals = get_unfiltered_reveals()?;
+    let reveal_count = reveals.len();
 
-    if reveals.len() != 1 {
-        elog!(
-            "Expected exactly one reveal (replication factor 1), found {}",
-            reveals.len()
-        );
-        return Err(anyhow::anyhow!("Invalid number of reveals"));
+    let mut valid_prices: Vec<u128> = Vec::new();
+    for reveal in &reveals {
+        match decode_price(&reveal.body.reveal) {
+            Ok(price) => valid_prices.push(price),
+            Err(err) => elog!("Reveal decode failed: {err}"),
+        }
     }
 
-    let http_response: HttpFetchResponse = serde_json::from_slice(&reveals[0].body.reveal)?;
-
-    if !http_response.is_ok() {
-        elog!(
-            "HTTP Response was rejected: {} - {}",
-            http_response.status,
-            String::from_utf8(http_response.bytes.clone())?
+    let quorum = reveal_count.div_ceil(2).max(1);
+    if valid_prices.len() < quorum {
+        Process::error(
+            format!(
+                "Only {} of {reveal_count} reveal(s) were valid, need at least {quorum}",
+                valid_prices.len()
+            )
+            .as_bytes(),
         );
-        return Err(anyhow::anyhow!("HTTP response not OK"));
+        return Err(anyhow::anyhow!("Insufficient valid reveals for quorum"));
     }
 
-    let response_data = serde_json::from_slice::<VvsPairsResponse>(&http_response.bytes)?;
+    let price_scaled = robust_median(&valid_prices)?;
 
-    let mut selected_price: Option<String> = None;
-    let mut selected_liquidity: Option<u128> = None;
+    log!(
+        "Aggregated WCRO/USD price from {} of {reveal_count} reveal(s): {price_scaled}",
+        valid_prices.len()
+    );
 
-    for pair in response_data.data.values() {
-        if pair.base_symbol != TARGET_BASE_SYMBOL || pair.quote_symbol != TARGET_QUOTE_SYMBOL {
-            continue;
-        }
+    let reveal = encode_reveal(AssetKind::DexPair, PRICE_DECIMALS as u8, price_scaled);
+    Process::success(&reveal);
 
-        let liquidity = match parse_decimal_to_u128(&pair.liquidity, LIQUIDITY_DECIMALS) {
-            Some(value) => value,
-            None => continue,
-        };
+    Ok(())
+}
 
-        let should_select = match selected_liquidity {
-            Some(current) => liquidity > current,
-            None => true,
-        };
+/// Decodes a single reveal's [`RpcPayload`] into a scaled WCRO/USD price,
+/// computing it from the pair's reserves the way `price_from_v2`-style code
+/// elsewhere in the repo does rather than expecting an off-chain REST quote.
+fn decode_price(reveal: &[u8]) -> Result<u128> {
+    let payload: RpcPayload = serde_json::from_slice(reveal)?;
 
-        if should_select {
-            selected_liquidity = Some(liquidity);
-            selected_price = Some(pair.price.clone());
-        }
+    let reserves_bytes =
+        hex_to_bytes(&payload.reserves_result).ok_or_else(|| anyhow!("Failed to parse reserves hex"))?;
+    if reserves_bytes.len() < 64 {
+        return Err(anyhow!("Reserves result too short"));
+    }
+    let reserve0 = U256::from_big_endian(&reserves_bytes[0..32]);
+    let reserve1 = U256::from_big_endian(&reserves_bytes[32..64]);
+
+    let (base_reserve, quote_reserve) = if payload.token0.eq_ignore_ascii_case(WCRO_ADDRESS) {
+        (reserve0, reserve1)
+    } else if payload.token0.eq_ignore_ascii_case(USDC_ADDRESS) {
+        (reserve1, reserve0)
+    } else {
+        return Err(anyhow!("token0 {} is neither WCRO nor USDC", payload.token0));
+    };
+
+    if base_reserve.is_zero() {
+        return Err(anyhow!("Base reserve is zero"));
     }
 
-    let price_str = selected_price.ok_or_else(|| {
-        anyhow::anyhow!("No WCRO/USDC pool found in VVS response")
-    })?;
-
-    let price_scaled = parse_decimal_to_u128(&price_str, PRICE_DECIMALS)
-        .ok_or_else(|| anyhow::anyhow!("Failed to parse WCRO/USD price"))?;
-
-    log!("Selected WCRO/USD price: {price_str} (scaled: {price_scaled})");
-
-    let result = ethabi::encode(&[Token::Uint(U256::from(price_scaled))]);
-    Process::success(&result);
+    // price_scaled = quote_reserve * 10^(WCRO_DECIMALS + PRICE_DECIMALS) /
+    //                (base_reserve * 10^USDC_DECIMALS)
+    let scale = U256::from(10u8).pow(U256::from(WCRO_DECIMALS + PRICE_DECIMALS));
+    let quote_scale = U256::from(10u8).pow(U256::from(USDC_DECIMALS));
+    let numerator = quote_reserve.saturating_mul(scale);
+    let denominator = base_reserve.saturating_mul(quote_scale);
+    let price_scaled = numerator / denominator;
 
-    Ok(())
+    u128::try_from(price_scaled).map_err(|_| anyhow!("Aggregated price exceeds u128 range"))
 }
 
-fn parse_decimal_to_u128(value: &str, scale: u32) -> Option<u128> {
-    let trimmed = value.trim();
-    if trimmed.is_empty() {
+fn hex_to_bytes(value: &str) -> Option<Vec<u8>> {
+    let cleaned = value.strip_prefix("0x").unwrap_or(value);
+    if cleaned.len() % 2 != 0 {
         return None;
     }
+    (0..cleaned.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&cleaned[i..i + 2], 16).ok())
+        .collect()
+}
 
-    let mut parts = trimmed.split('.');
-    let whole = parts.next().unwrap_or("0");
-    let fraction = parts.next().unwrap_or("");
-
-    if parts.next().is_some() {
-        return None;
+/// Computes the median of `prices`, rejecting any sample more than
+/// `OUTLIER_K` scaled median-absolute-deviations away from the initial
+/// median before taking the final median of survivors. Keeps every sample
+/// if `MAD == 0` (the honest majority agrees exactly) or there are too few
+/// samples to measure a meaningful spread.
+fn robust_median(prices: &[u128]) -> Result<u128> {
+    let mut sorted = prices.to_vec();
+    sorted.sort_unstable();
+
+    if sorted.len() < 3 {
+        return Ok(median_sorted(&sorted));
     }
 
-    if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
-        return None;
+    let med = median_sorted(&sorted);
+    let mut deviations: Vec<u128> = sorted.iter().map(|price| abs_diff(*price, med)).collect();
+    deviations.sort_unstable();
+    let mad = median_sorted(&deviations);
+
+    if mad == 0 {
+        return Ok(med);
     }
 
-    let mut combined = String::new();
-    combined.push_str(whole);
+    let threshold = OUTLIER_K * mad * MAD_SCALE_NUM / MAD_SCALE_DEN;
+    let survivors: Vec<u128> = sorted
+        .iter()
+        .copied()
+        .filter(|price| abs_diff(*price, med) <= threshold)
+        .collect();
 
-    if scale > 0 {
-        let mut frac = fraction.to_string();
-        let target_len = scale as usize;
-        if frac.len() < target_len {
-            frac.push_str(&"0".repeat(target_len - frac.len()));
-        } else if frac.len() > target_len {
-            frac.truncate(target_len);
-        }
-        combined.push_str(&frac);
+    let rejected = sorted.len() - survivors.len();
+    if rejected > 0 {
+        log!("Rejected {rejected} outlier reveal(s) (k={OUTLIER_K}, MAD={mad})");
+    }
+
+    if survivors.len() < MIN_SURVIVORS {
+        Process::error("No consensus among revealed prices after outlier rejection".as_bytes());
+        return Err(anyhow::anyhow!(
+            "No consensus among revealed prices after outlier rejection"
+        ));
     }
 
-    let trimmed_combined = combined.trim_start_matches('0');
-    if trimmed_combined.is_empty() {
-        return Some(0);
+    Ok(median_sorted(&survivors))
+}
+
+fn median_sorted(values: &[u128]) -> u128 {
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2
+    } else {
+        values[mid]
     }
+}
 
-    trimmed_combined.parse::<u128>().ok()
+fn abs_diff(a: u128, b: u128) -> u128 {
+    if a > b { a - b } else { b - a }
 }